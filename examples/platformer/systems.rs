@@ -21,13 +21,18 @@ pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
 
 pub fn pause_physics_during_load(
     mut level_events: EventReader<LevelEvent>,
+    mut world_events: EventReader<LdtkWorldEvent>,
     mut physics_time: ResMut<PhysicsTime>,
 ) {
     for event in level_events.iter() {
-        match event {
-            LevelEvent::SpawnTriggered(_) => physics_time.set_scale(0.),
-            LevelEvent::Transformed(_) => physics_time.set_scale(1.),
-            _ => (),
+        if let LevelEvent::SpawnTriggered(_) = event {
+            physics_time.set_scale(0.);
+        }
+    }
+
+    for event in world_events.iter() {
+        if let LdtkWorldEvent::Ready(_) = event {
+            physics_time.set_scale(1.);
         }
     }
 }
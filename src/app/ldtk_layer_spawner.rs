@@ -0,0 +1,685 @@
+use crate::{
+    app::{
+        LdtkEntity, LdtkEntityMap, LdtkIntCellMap, PhantomLdtkEntity, PhantomLdtkEntityTrait,
+        PhantomLdtkIntCell, PhantomLdtkIntCellTrait,
+    },
+    assets::TilesetMap,
+    components::*,
+    ldtk::{EntityDefinition, LayerDefinition, LayerInstance, Level, TilesetDefinition, Type},
+    resources::{IntGridMode, IntGridStorage, LdtkSettings, WhiteImages},
+    tile_makers::*,
+    utils::*,
+};
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_ecs_tilemap::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+#[allow(unused_imports)]
+use crate::app::{ldtk_int_cell::LdtkIntCell, register_ldtk_objects::RegisterLdtkObjects};
+
+pub(crate) const CHUNK_SIZE: ChunkSize = ChunkSize(32, 32);
+
+/// Provides a function for spawning an LDtk layer of a level.
+///
+/// Register implementations of this trait for particular layer identifiers or layer [Type]s via
+/// [RegisterLdtkObjects] functions on your [App].
+/// Then, they will be used instead of the [DefaultLdtkLayerSpawner] when spawning those layers.
+///
+/// This lets you replace how particular layers are spawned, like rendering a Tile layer as a
+/// single baked image, or skipping the rendering of IntGrid layers entirely.
+/// You can also augment the default behavior by calling [DefaultLdtkLayerSpawner] in your own
+/// implementation.
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_ecs_ldtk::{app::*, prelude::*};
+///
+/// struct CountingLayerSpawner;
+///
+/// impl LdtkLayerSpawner for CountingLayerSpawner {
+///     fn spawn_layer(
+///         &self,
+///         commands: &mut Commands,
+///         meshes: &mut ResMut<Assets<Mesh>>,
+///         context: &mut LayerSpawnContext,
+///         layer_instance: &LayerInstance,
+///     ) {
+///         info!("{} has {} tiles", layer_instance.identifier, layer_instance.grid_tiles.len());
+///
+///         DefaultLdtkLayerSpawner.spawn_layer(commands, meshes, context, layer_instance);
+///     }
+/// }
+///
+/// fn main() {
+///     App::empty()
+///         .add_plugin(LdtkPlugin)
+///         .register_ldtk_layer_spawner_for_type(ldtk::Type::Tiles, CountingLayerSpawner)
+///         // add other systems, plugins, resources...
+///         .run();
+/// }
+/// ```
+pub trait LdtkLayerSpawner {
+    /// Spawns the given layer of [LayerSpawnContext::level].
+    ///
+    /// Every bevy_ecs_tilemap layer spawned should use an id obtained from
+    /// [LayerSpawnContext::next_layer_id], and be added to [LayerSpawnContext::map].
+    /// Any other entities should be spawned as descendants of [LayerSpawnContext::level_entity].
+    fn spawn_layer(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        context: &mut LayerSpawnContext,
+        layer_instance: &LayerInstance,
+    );
+}
+
+/// The level-wide information and resources available to an [LdtkLayerSpawner].
+pub struct LayerSpawnContext<'a> {
+    pub asset_server: &'a AssetServer,
+    pub images: &'a mut Assets<Image>,
+    pub texture_atlases: &'a mut Assets<TextureAtlas>,
+    pub ldtk_entity_map: &'a LdtkEntityMap,
+    pub ldtk_int_cell_map: &'a LdtkIntCellMap,
+    pub entity_definition_map: &'a HashMap<i32, &'a EntityDefinition>,
+    pub layer_definition_map: &'a HashMap<i32, &'a LayerDefinition>,
+    pub tileset_map: &'a TilesetMap,
+    pub tileset_definition_map: &'a HashMap<i32, &'a TilesetDefinition>,
+    /// The [Worldly] entities that already exist, which should not be spawned again.
+    pub worldly_set: &'a HashSet<Worldly>,
+    pub ldtk_settings: &'a LdtkSettings,
+    pub level: &'a Level,
+    pub level_entity: Entity,
+    /// The bevy_ecs_tilemap [Map] of the level, which tilemap layers should be added to.
+    pub map: &'a mut Map,
+    pub(crate) white_images: &'a mut WhiteImages,
+    /// The z value of the layer being spawned.
+    ///
+    /// This is the id of the first tilemap layer spawned for the layer.
+    /// See [LdtkSettings::layer_z_step].
+    pub layer_z: u16,
+    pub(crate) next_layer_id: u16,
+}
+
+impl<'a> LayerSpawnContext<'a> {
+    /// Reserves a bevy_ecs_tilemap layer id for a new layer.
+    ///
+    /// The ids are consecutive, starting from [LayerSpawnContext::layer_z], and also determine the
+    /// layers' z ordering.
+    /// Entities that aren't tilemap layers, like the contents of an Entities layer, can also use
+    /// one as their z value.
+    pub fn next_layer_id(&mut self) -> u16 {
+        let layer_id = self.next_layer_id;
+        self.next_layer_id += 1;
+        layer_id
+    }
+
+    /// Returns a white image `size` pixels wide and tall, for untextured tilemap layers whose
+    /// tiles are that size.
+    ///
+    /// These images are shared by all levels, so they shouldn't be modified.
+    pub fn white_image(&mut self, size: u32) -> Handle<Image> {
+        self.white_images.get_or_add(size, self.images)
+    }
+}
+
+/// The [LdtkLayerSpawner] used for any layer without a registered spawner.
+///
+/// Entities layers are spawned as an entity with an [LdtkLayer] component, with the layer's
+/// [EntityInstance]s as its children.
+/// Tile, AutoTile and IntGrid layers are spawned as one or more bevy_ecs_tilemap layers.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct DefaultLdtkLayerSpawner;
+
+impl LdtkLayerSpawner for DefaultLdtkLayerSpawner {
+    fn spawn_layer(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        context: &mut LayerSpawnContext,
+        layer_instance: &LayerInstance,
+    ) {
+        match layer_instance.layer_instance_type {
+            Type::Entities => spawn_entity_layer(commands, context, layer_instance),
+            _ => spawn_tile_layer(
+                commands,
+                meshes,
+                context,
+                layer_instance,
+                context.ldtk_settings.int_grid_mode,
+            ),
+        }
+    }
+}
+
+/// An [LdtkLayerSpawner] for IntGrid layers that uses the given [IntGridMode] instead of
+/// [LdtkSettings::int_grid_mode].
+///
+/// Otherwise, this spawns layers just like the [DefaultLdtkLayerSpawner].
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_ecs_ldtk::{app::*, prelude::*};
+///
+/// fn main() {
+///     App::empty()
+///         .add_plugin(LdtkPlugin)
+///         .register_ldtk_layer_spawner_for_layer(
+///             "Collisions",
+///             IntGridLayerSpawner {
+///                 mode: IntGridMode::DataOnly,
+///             },
+///         )
+///         // add other systems, plugins, resources...
+///         .run();
+/// }
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct IntGridLayerSpawner {
+    pub mode: IntGridMode,
+}
+
+impl LdtkLayerSpawner for IntGridLayerSpawner {
+    fn spawn_layer(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        context: &mut LayerSpawnContext,
+        layer_instance: &LayerInstance,
+    ) {
+        match layer_instance.layer_instance_type {
+            Type::Entities => spawn_entity_layer(commands, context, layer_instance),
+            _ => spawn_tile_layer(commands, meshes, context, layer_instance, self.mode),
+        }
+    }
+}
+
+/// Iterates over the nonzero IntGrid values of a layer, along with their positions.
+fn int_grid_cells(layer_instance: &LayerInstance) -> impl Iterator<Item = (TilePos, i32)> + '_ {
+    layer_instance
+        .int_grid_csv
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| **v != 0)
+        .map(|(i, v)| {
+            let tile_pos = int_grid_index_to_tile_pos(
+                i,
+                layer_instance.c_wid as u32,
+                layer_instance.c_hei as u32,
+            ).expect("int_grid_csv indices should be within the bounds of 0..(layer_widthd * layer_height)");
+
+            (tile_pos, *v)
+        })
+}
+
+/// Whether the IntGrid value should be spawned as an entity, according to
+/// [LdtkSettings::int_grid_storage].
+fn int_grid_cell_needs_entity(
+    context: &LayerSpawnContext,
+    layer_instance: &LayerInstance,
+    value: i32,
+) -> bool {
+    match context.ldtk_settings.int_grid_storage {
+        IntGridStorage::Entities => true,
+        IntGridStorage::Compact => ldtk_map_get(
+            layer_instance.identifier.clone(),
+            value,
+            context.ldtk_int_cell_map,
+        )
+        .is_some(),
+    }
+}
+
+/// Inserts the [LdtkIntCell] bundle registered for an IntGrid value into the given entity, placing
+/// it at the center of its cell as a child of the layer entity.
+fn insert_int_grid_cell_bundle(
+    entity_commands: &mut EntityCommands,
+    ldtk_int_cell_map: &LdtkIntCellMap,
+    layer_instance: &LayerInstance,
+    tile_pos: TilePos,
+    value: i32,
+    z: f32,
+    layer_entity: Entity,
+) {
+    let translation =
+        tile_pos_to_translation_centered(tile_pos, IVec2::splat(layer_instance.grid_size))
+            .extend(z);
+
+    let default_ldtk_int_cell: Box<dyn PhantomLdtkIntCellTrait> =
+        Box::new(PhantomLdtkIntCell::<IntGridCellBundle>::new());
+
+    ldtk_map_get_or_default(
+        layer_instance.identifier.clone(),
+        value,
+        &default_ldtk_int_cell,
+        ldtk_int_cell_map,
+    )
+    .evaluate(entity_commands, IntGridCell { value }, layer_instance);
+
+    entity_commands
+        .insert(GridCoords::from(tile_pos))
+        .insert(Transform::from_translation(translation))
+        .insert(GlobalTransform::default())
+        .insert(Parent(layer_entity));
+}
+
+fn spawn_entity_layer(
+    commands: &mut Commands,
+    context: &mut LayerSpawnContext,
+    layer_instance: &LayerInstance,
+) {
+    let layer_id = context.next_layer_id();
+
+    let layer_entity = commands
+        .spawn()
+        .insert_bundle((
+            LdtkLayer::from_layer_instance(layer_instance, layer_id as f32),
+            Transform::default(),
+            GlobalTransform::default(),
+        ))
+        .id();
+
+    commands
+        .entity(context.level_entity)
+        .push_children(&[layer_entity]);
+
+    commands.entity(layer_entity).with_children(|commands| {
+        for entity_instance in &layer_instance.entity_instances {
+            let transform = calculate_transform_from_entity_instance(
+                entity_instance,
+                context.entity_definition_map,
+                context.level.px_hei,
+                layer_id as f32,
+            );
+            // Note: entities do not seem to be affected visually by layer offsets in
+            // the editor, so no layer offset is added to the transform here.
+
+            let mut entity_commands = commands.spawn();
+
+            let (tileset, tileset_definition) = match &entity_instance.tile {
+                Some(t) => (
+                    context.tileset_map.get(&t.tileset_uid),
+                    context.tileset_definition_map.get(&t.tileset_uid).copied(),
+                ),
+                None => (None, None),
+            };
+
+            let predicted_worldly = Worldly::bundle_entity(
+                entity_instance,
+                layer_instance,
+                tileset,
+                tileset_definition,
+                context.asset_server,
+                context.texture_atlases,
+            );
+
+            if !context.worldly_set.contains(&predicted_worldly) {
+                let default_ldtk_entity: Box<dyn PhantomLdtkEntityTrait> =
+                    Box::new(PhantomLdtkEntity::<EntityInstanceBundle>::new());
+
+                ldtk_map_get_or_default(
+                    layer_instance.identifier.clone(),
+                    entity_instance.identifier.clone(),
+                    &default_ldtk_entity,
+                    context.ldtk_entity_map,
+                )
+                .evaluate(
+                    &mut entity_commands,
+                    entity_instance,
+                    layer_instance,
+                    tileset,
+                    tileset_definition,
+                    context.asset_server,
+                    context.texture_atlases,
+                );
+
+                entity_commands
+                    .insert(transform)
+                    .insert(GlobalTransform::default());
+            }
+        }
+    });
+}
+
+fn spawn_tile_layer(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    context: &mut LayerSpawnContext,
+    layer_instance: &LayerInstance,
+    int_grid_mode: IntGridMode,
+) {
+    // The remaining layers have a lot of shared code.
+    // This is because:
+    // 1. There is virtually no difference between AutoTile and Tile layers
+    // 2. IntGrid layers can sometimes have AutoTile functionality
+
+    let map_size = MapSize(
+        (layer_instance.c_wid as f32 / CHUNK_SIZE.0 as f32).ceil() as u32,
+        (layer_instance.c_hei as f32 / CHUNK_SIZE.1 as f32).ceil() as u32,
+    );
+
+    let tileset_definition = layer_instance
+        .tileset_def_uid
+        .map(|u| context.tileset_definition_map.get(&u).unwrap());
+
+    let layer_definition = context
+        .layer_definition_map
+        .get(&layer_instance.layer_def_uid)
+        .expect("Encountered layer without definition");
+
+    let tile_pivot = Vec2::new(layer_definition.tile_pivot_x, layer_definition.tile_pivot_y);
+
+    let tile_size = match tileset_definition {
+        Some(tileset_definition) => tileset_definition.tile_grid_size,
+        None => layer_instance.grid_size,
+    };
+
+    let grid_tile_size = TileSize(
+        layer_instance.grid_size as f32,
+        layer_instance.grid_size as f32,
+    );
+
+    let grid_texture_size = TextureSize(
+        layer_instance.grid_size as f32,
+        layer_instance.grid_size as f32,
+    );
+
+    let texture_size = match tileset_definition {
+        Some(tileset_definition) => TextureSize(
+            tileset_definition.px_wid as f32,
+            tileset_definition.px_hei as f32,
+        ),
+        None => grid_texture_size,
+    };
+
+    #[cfg_attr(not(feature = "atlas"), allow(unused_mut))]
+    let mut settings = LayerSettings::new(
+        map_size,
+        CHUNK_SIZE,
+        TileSize(tile_size as f32, tile_size as f32),
+        texture_size,
+    );
+
+    if let Some(tileset_definition) = tileset_definition {
+        if tileset_definition.spacing != 0 {
+            #[cfg(not(feature = "atlas"))]
+            {
+                warn!("Tile spacing on Tile and AutoTile layers requires the \"atlas\" feature");
+            }
+
+            #[cfg(feature = "atlas")]
+            {
+                settings.tile_spacing = Vec2::splat(tileset_definition.spacing as f32);
+            }
+        }
+    }
+
+    let image_handle = match tileset_definition {
+        Some(tileset_definition) => context
+            .tileset_map
+            .get(&tileset_definition.uid)
+            .unwrap()
+            .clone(),
+        None => context.white_image(layer_instance.grid_size as u32),
+    };
+
+    let layer_offset = Vec3::new(
+        layer_instance.px_total_offset_x as f32,
+        -layer_instance.px_total_offset_y as f32,
+        0.,
+    );
+
+    let mut grid_tiles = layer_instance.grid_tiles.clone();
+    grid_tiles.extend(layer_instance.auto_layer_tiles.clone());
+
+    // bevy_ecs_tilemap places tiles on a grid the same size as the tiles
+    // themselves.
+    // So, tiles that are larger or smaller than the layer's grid can't share a
+    // tilemap layer with the IntGrid values, and are spawned separately below.
+    let tiles_match_grid = tile_size == layer_instance.grid_size;
+
+    let is_int_grid = layer_instance.layer_instance_type == Type::IntGrid;
+
+    if is_int_grid {
+        match int_grid_mode {
+            IntGridMode::Full => {
+                // The current spawning of IntGrid layers doesn't allow using
+                // LayerBuilder::new_batch().
+                // So, the actual LayerBuilder usage diverges greatly here
+
+                let (int_grid_settings, int_grid_image_handle, stacked_grid_tiles) =
+                    if tiles_match_grid {
+                        let mut stacked_grid_tiles = layer_grid_tiles(
+                            grid_tiles.clone(),
+                            context.ldtk_settings.max_tile_stack_depth,
+                        );
+
+                        // IntGrid layers need at least one tilemap layer for their cells
+                        if stacked_grid_tiles.is_empty() {
+                            stacked_grid_tiles.push(Vec::new());
+                        }
+
+                        (settings, image_handle.clone(), stacked_grid_tiles)
+                    } else {
+                        let mut int_grid_settings = settings;
+                        int_grid_settings.tile_size = grid_tile_size;
+                        int_grid_settings.texture_size = grid_texture_size;
+                        (
+                            int_grid_settings,
+                            context.white_image(layer_instance.grid_size as u32),
+                            vec![Vec::new()],
+                        )
+                    };
+
+                for (i, grid_tiles) in stacked_grid_tiles.into_iter().enumerate() {
+                    let layer_id = context.next_layer_id();
+
+                    let (mut layer_builder, layer_entity) = LayerBuilder::<TileGridBundle>::new(
+                        commands,
+                        int_grid_settings,
+                        context.map.id,
+                        layer_id,
+                    );
+
+                    match tileset_definition {
+                        Some(_) if tiles_match_grid => {
+                            set_all_tiles_with_func(
+                                &mut layer_builder,
+                                tile_bundle_maker_with_opacity(
+                                    tile_pos_to_tile_bundle_maker(
+                                        tile_pos_to_int_grid_with_grid_tiles_tile_maker(
+                                            &grid_tiles,
+                                            &layer_instance.int_grid_csv,
+                                            layer_instance.c_wid,
+                                            layer_instance.c_hei,
+                                            layer_instance.grid_size,
+                                        ),
+                                    ),
+                                    layer_instance.opacity,
+                                ),
+                            );
+                        }
+                        Some(_) => {
+                            set_all_tiles_with_func(
+                                &mut layer_builder,
+                                tile_pos_to_tile_bundle_maker(
+                                    tile_pos_to_tile_if_int_grid_nonzero_maker(
+                                        tile_pos_to_invisible_tile,
+                                        &layer_instance.int_grid_csv,
+                                        layer_instance.c_wid,
+                                        layer_instance.c_hei,
+                                    ),
+                                ),
+                            );
+                        }
+                        None => {
+                            set_all_tiles_with_func(
+                                &mut layer_builder,
+                                tile_bundle_maker_with_opacity(
+                                    tile_pos_to_tile_bundle_maker(
+                                        tile_pos_to_int_grid_colored_tile_maker(
+                                            &layer_instance.int_grid_csv,
+                                            &layer_definition.int_grid_values,
+                                            layer_instance.c_wid,
+                                            layer_instance.c_hei,
+                                        ),
+                                    ),
+                                    layer_instance.opacity,
+                                ),
+                            );
+                        }
+                    }
+
+                    if i == 0 {
+                        commands
+                            .entity(layer_entity)
+                            .insert(IntGrid::from_layer_instance(layer_instance));
+
+                        for (tile_pos, value) in
+                            int_grid_cells(layer_instance).filter(|(_, value)| {
+                                int_grid_cell_needs_entity(context, layer_instance, *value)
+                            })
+                        {
+                            let tile_entity =
+                                layer_builder.get_tile_entity(commands, tile_pos).unwrap();
+
+                            insert_int_grid_cell_bundle(
+                                &mut commands.entity(tile_entity),
+                                context.ldtk_int_cell_map,
+                                layer_instance,
+                                tile_pos,
+                                value,
+                                layer_id as f32,
+                                layer_entity,
+                            );
+                        }
+                    }
+
+                    let layer_bundle =
+                        layer_builder.build(commands, meshes, int_grid_image_handle.clone());
+
+                    commands.entity(layer_entity).insert_bundle(layer_bundle);
+
+                    commands.entity(layer_entity).insert_bundle((
+                        Transform::from_translation(layer_offset),
+                        LdtkLayer::from_layer_instance(layer_instance, layer_id as f32),
+                    ));
+
+                    context.map.add_layer(commands, layer_id, layer_entity);
+                }
+            }
+            IntGridMode::DataOnly => {
+                let layer_id = context.next_layer_id();
+
+                let layer_entity = commands
+                    .spawn()
+                    .insert_bundle((
+                        LdtkLayer::from_layer_instance(layer_instance, layer_id as f32),
+                        IntGrid::from_layer_instance(layer_instance),
+                        Transform::from_translation(layer_offset),
+                        GlobalTransform::default(),
+                    ))
+                    .id();
+
+                commands
+                    .entity(context.level_entity)
+                    .push_children(&[layer_entity]);
+
+                for (tile_pos, value) in int_grid_cells(layer_instance).filter(|(_, value)| {
+                    int_grid_cell_needs_entity(context, layer_instance, *value)
+                }) {
+                    insert_int_grid_cell_bundle(
+                        &mut commands.spawn(),
+                        context.ldtk_int_cell_map,
+                        layer_instance,
+                        tile_pos,
+                        value,
+                        layer_id as f32,
+                        layer_entity,
+                    );
+                }
+            }
+            IntGridMode::RenderOnly => {
+                // With a tileset, only the AutoLayer tiles are rendered, which happens below.
+                if tileset_definition.is_none() {
+                    let layer_id = context.next_layer_id();
+
+                    let layer_entity = LayerBuilder::<TileGridBundle>::new_batch(
+                        commands,
+                        settings,
+                        meshes,
+                        context.white_image(layer_instance.grid_size as u32),
+                        context.map.id,
+                        layer_id,
+                        tile_bundle_maker_with_opacity(
+                            tile_pos_to_tile_bundle_maker(tile_pos_to_int_grid_colored_tile_maker(
+                                &layer_instance.int_grid_csv,
+                                &layer_definition.int_grid_values,
+                                layer_instance.c_wid,
+                                layer_instance.c_hei,
+                            )),
+                            layer_instance.opacity,
+                        ),
+                    );
+
+                    commands.entity(layer_entity).insert_bundle((
+                        Transform::from_translation(layer_offset),
+                        LdtkLayer::from_layer_instance(layer_instance, layer_id as f32),
+                    ));
+
+                    context.map.add_layer(commands, layer_id, layer_entity);
+                }
+            }
+        }
+    }
+
+    if !(is_int_grid && int_grid_mode == IntGridMode::Full && tiles_match_grid) {
+        // Tiles are positioned relative to their grid cell using the layer's tile
+        // pivot, exactly like in the editor, rather than scaling the entire layer.
+        for grid_tiles in layer_grid_tiles(grid_tiles, context.ldtk_settings.max_tile_stack_depth) {
+            for pivoted_tile_grid in pivoted_tile_grids(
+                &grid_tiles,
+                layer_instance.c_wid,
+                layer_instance.c_hei,
+                layer_instance.grid_size,
+                tile_size,
+                tile_pivot,
+            ) {
+                let layer_id = context.next_layer_id();
+
+                let mut settings = settings;
+                settings.map_size = MapSize(
+                    (pivoted_tile_grid.size_in_tiles.x as f32 / CHUNK_SIZE.0 as f32).ceil() as u32,
+                    (pivoted_tile_grid.size_in_tiles.y as f32 / CHUNK_SIZE.1 as f32).ceil() as u32,
+                );
+
+                let layer_entity = LayerBuilder::<TileGridBundle>::new_batch(
+                    commands,
+                    settings,
+                    meshes,
+                    image_handle.clone(),
+                    context.map.id,
+                    layer_id,
+                    tile_bundle_maker_with_opacity(
+                        tile_pos_to_pivoted_tile_bundle_maker(
+                            &pivoted_tile_grid,
+                            layer_instance.c_hei,
+                            layer_instance.grid_size,
+                        ),
+                        layer_instance.opacity,
+                    ),
+                );
+
+                commands.entity(layer_entity).insert_bundle((
+                    Transform::from_translation(
+                        layer_offset + pivoted_tile_grid.origin.as_vec2().extend(0.),
+                    ),
+                    LdtkLayer::from_layer_instance(layer_instance, layer_id as f32),
+                ));
+
+                context.map.add_layer(commands, layer_id, layer_entity);
+            }
+        }
+    }
+}
+
+/// Used by [RegisterLdtkObjects] to associate layer [Type]s and identifiers with
+/// [LdtkLayerSpawner]s.
+pub type LdtkLayerSpawnerMap = HashMap<(Option<Type>, Option<String>), Box<dyn LdtkLayerSpawner>>;
@@ -2,8 +2,10 @@
 
 mod ldtk_entity;
 mod ldtk_int_cell;
+mod ldtk_layer_spawner;
 mod register_ldtk_objects;
 
 pub use ldtk_entity::*;
 pub use ldtk_int_cell::*;
+pub use ldtk_layer_spawner::*;
 pub use register_ldtk_objects::*;
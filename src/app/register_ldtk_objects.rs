@@ -1,4 +1,7 @@
-use crate::{app::ldtk_entity::*, app::ldtk_int_cell::*};
+use crate::{
+    app::{ldtk_entity::*, ldtk_int_cell::*, ldtk_layer_spawner::*},
+    ldtk::Type,
+};
 use bevy::prelude::*;
 
 /// Provides functions to register [Bundle]s to bevy's [App] for particular LDtk layer identifiers,
@@ -182,6 +185,57 @@ pub trait RegisterLdtkObjects {
     fn register_default_ldtk_int_cell<B: LdtkIntCell + Bundle>(&mut self) -> &mut Self {
         self.register_ldtk_int_cell_for_layer_optional::<B>(None, None)
     }
+
+    /// Used internally by all the other LDtk layer spawner registration functions.
+    ///
+    /// Similar to [RegisterLdtkObjects::register_ldtk_layer_spawner_for_layer], except it provides
+    /// defaulting functionality:
+    /// - Setting `layer_type` to [None] will make the registration apply to any layer type.
+    /// - Setting `layer_identifier` to [None] will make the registration apply to any layer.
+    ///
+    /// Registrations are prioritized in order of most to least specific:
+    /// 1. `layer_type` and `layer_identifier` are specified
+    /// 2. Just `layer_identifier` is specified
+    /// 3. Just `layer_type` is specified
+    /// 4. Neither `layer_type` nor `layer_identifier` are specified
+    ///
+    /// Layers that don't match any registration are spawned with [DefaultLdtkLayerSpawner].
+    fn register_ldtk_layer_spawner_optional<S: LdtkLayerSpawner + 'static>(
+        &mut self,
+        layer_type: Option<Type>,
+        layer_identifier: Option<String>,
+        spawner: S,
+    ) -> &mut Self;
+
+    /// Registers an [LdtkLayerSpawner] to spawn layers with the given identifier in an LDtk file.
+    ///
+    /// See [LdtkLayerSpawner] for an example.
+    fn register_ldtk_layer_spawner_for_layer<S: LdtkLayerSpawner + 'static>(
+        &mut self,
+        layer_identifier: &str,
+        spawner: S,
+    ) -> &mut Self {
+        self.register_ldtk_layer_spawner_optional(None, Some(layer_identifier.to_string()), spawner)
+    }
+
+    /// Similar to [RegisterLdtkObjects::register_ldtk_layer_spawner_for_layer], except it applies
+    /// the registration to all layers of the given [Type].
+    fn register_ldtk_layer_spawner_for_type<S: LdtkLayerSpawner + 'static>(
+        &mut self,
+        layer_type: Type,
+        spawner: S,
+    ) -> &mut Self {
+        self.register_ldtk_layer_spawner_optional(Some(layer_type), None, spawner)
+    }
+
+    /// Similar to [RegisterLdtkObjects::register_ldtk_layer_spawner_for_layer], except it applies
+    /// the registration to all layers, replacing [DefaultLdtkLayerSpawner].
+    fn register_default_ldtk_layer_spawner<S: LdtkLayerSpawner + 'static>(
+        &mut self,
+        spawner: S,
+    ) -> &mut Self {
+        self.register_ldtk_layer_spawner_optional(None, None, spawner)
+    }
 }
 
 impl RegisterLdtkObjects for App {
@@ -222,6 +276,30 @@ impl RegisterLdtkObjects for App {
         }
         self
     }
+
+    fn register_ldtk_layer_spawner_optional<S: LdtkLayerSpawner + 'static>(
+        &mut self,
+        layer_type: Option<Type>,
+        layer_identifier: Option<String>,
+        spawner: S,
+    ) -> &mut Self {
+        let new_entry = Box::new(spawner);
+        match self
+            .world
+            .get_non_send_resource_mut::<LdtkLayerSpawnerMap>()
+        {
+            Some(mut entries) => {
+                entries.insert((layer_type, layer_identifier), new_entry);
+            }
+            None => {
+                let mut spawner_map = LdtkLayerSpawnerMap::new();
+                spawner_map.insert((layer_type, layer_identifier), new_entry);
+                self.world
+                    .insert_non_send::<LdtkLayerSpawnerMap>(spawner_map);
+            }
+        }
+        self
+    }
 }
 
 #[cfg(test)]
@@ -313,4 +391,36 @@ mod tests {
 
         assert!(ldtk_int_cell_map.contains_key(&(None, None)));
     }
+
+    struct LayerSpawner;
+
+    impl LdtkLayerSpawner for LayerSpawner {
+        fn spawn_layer(
+            &self,
+            _: &mut Commands,
+            _: &mut ResMut<Assets<Mesh>>,
+            _: &mut LayerSpawnContext,
+            _: &LayerInstance,
+        ) {
+        }
+    }
+
+    #[test]
+    fn test_ldtk_layer_spawner_registrations() {
+        let mut app = App::new();
+        app.register_ldtk_layer_spawner_for_layer("layer", LayerSpawner)
+            .register_ldtk_layer_spawner_for_type(Type::IntGrid, LayerSpawner)
+            .register_default_ldtk_layer_spawner(LayerSpawner);
+
+        let ldtk_layer_spawner_map = app
+            .world
+            .get_non_send_resource::<LdtkLayerSpawnerMap>()
+            .unwrap();
+
+        assert!(ldtk_layer_spawner_map.contains_key(&(None, Some("layer".to_string()))));
+
+        assert!(ldtk_layer_spawner_map.contains_key(&(Some(Type::IntGrid), None)));
+
+        assert!(ldtk_layer_spawner_map.contains_key(&(None, None)));
+    }
 }
@@ -1,8 +1,14 @@
 //! Assets and AssetLoaders for loading ldtk files.
 
+#[allow(unused_imports)]
+use crate::ldtk::FieldValue;
 use crate::{
-    ldtk::{LdtkJson, Level},
-    resources::LevelSelection,
+    ldtk::{
+        deserialize_leniently, field_value_from_json, Definitions, EntityInstance, FieldInstance,
+        FieldValueError, LayerInstance, LdtkJson, Level,
+    },
+    resources::{ExternalLevelLoading, LdtkLoadErrorQueue, LevelSelection},
+    validation::{validate, validate_level, Severity},
 };
 use bevy::{
     asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
@@ -10,10 +16,434 @@ use bevy::{
     reflect::TypeUuid,
     utils::BoxedFuture,
 };
-use std::{collections::HashMap, path::Path};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
 
 #[allow(unused_imports)]
-use crate::components::LdtkWorldBundle;
+use crate::{
+    components::{LdtkWorldBundle, LevelSet},
+    resources::{LdtkLoadErrorEvent, LdtkSettings},
+};
+
+/// Where in an LDtk file an [LdtkLoadError] occurred.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct LdtkErrorLocation {
+    /// Path to the offending JSON value, like `$.levels[2].layerInstances[0]`.
+    pub json_path: String,
+    /// Identifier of the level containing the offending value, if any.
+    pub level: Option<String>,
+    /// Identifier of the entity containing the offending value, if any.
+    pub entity: Option<String>,
+    /// Identifier of the field containing the offending value, if any.
+    pub field: Option<String>,
+}
+
+impl fmt::Display for LdtkErrorLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.json_path)?;
+
+        let identifiers: Vec<String> = [
+            ("level", &self.level),
+            ("entity", &self.entity),
+            ("field", &self.field),
+        ]
+        .iter()
+        .filter_map(|(kind, identifier)| {
+            identifier
+                .as_ref()
+                .map(|identifier| format!("{} {:?}", kind, identifier))
+        })
+        .collect();
+
+        if !identifiers.is_empty() {
+            write!(f, " ({})", identifiers.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Errors that can occur while loading an [LdtkAsset] or [LdtkLevel].
+///
+/// These are logged by bevy's [AssetServer] when loading fails, and are also sent as
+/// [LdtkLoadErrorEvent]s.
+#[derive(Debug)]
+pub enum LdtkLoadError {
+    /// The file is not valid JSON.
+    Syntax(serde_json::Error),
+    /// The file is valid JSON, but some value doesn't have the structure LDtk files should have.
+    Structure {
+        location: LdtkErrorLocation,
+        source: serde_json::Error,
+    },
+    /// A field instance has a `__type` that is not supported by this plugin.
+    UnknownFieldType {
+        location: LdtkErrorLocation,
+        field_type: String,
+    },
+    /// A field instance has a `__value` that doesn't match its `__type`.
+    InvalidFieldValue {
+        location: LdtkErrorLocation,
+        field_type: String,
+        source: serde_json::Error,
+    },
+}
+
+impl LdtkLoadError {
+    /// Returns where in the file the error occurred, unless the file isn't valid JSON.
+    pub fn location(&self) -> Option<&LdtkErrorLocation> {
+        match self {
+            LdtkLoadError::Syntax(_) => None,
+            LdtkLoadError::Structure { location, .. }
+            | LdtkLoadError::UnknownFieldType { location, .. }
+            | LdtkLoadError::InvalidFieldValue { location, .. } => Some(location),
+        }
+    }
+}
+
+impl fmt::Display for LdtkLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LdtkLoadError::Syntax(e) => write!(f, "invalid JSON: {}", e),
+            LdtkLoadError::Structure { location, source } => {
+                write!(f, "invalid LDtk data at {}: {}", location, source)
+            }
+            LdtkLoadError::UnknownFieldType {
+                location,
+                field_type,
+            } => write!(f, "unknown field type {:?} at {}", field_type, location),
+            LdtkLoadError::InvalidFieldValue {
+                location,
+                field_type,
+                source,
+            } => write!(
+                f,
+                "invalid value for field type {:?} at {}: {}",
+                field_type, location, source
+            ),
+        }
+    }
+}
+
+impl Error for LdtkLoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LdtkLoadError::Syntax(source)
+            | LdtkLoadError::Structure { source, .. }
+            | LdtkLoadError::InvalidFieldValue { source, .. } => Some(source),
+            LdtkLoadError::UnknownFieldType { .. } => None,
+        }
+    }
+}
+
+/// Problems that lenient parsing recovers from, instead of failing.
+#[derive(Debug)]
+pub enum LdtkLoadWarning {
+    /// A level failed to parse, so it was skipped.
+    SkippedLevel(LdtkLoadError),
+    /// A field instance couldn't be re-typed, so it was kept as [FieldValue::Unknown].
+    UnknownField(LdtkLoadError),
+}
+
+impl fmt::Display for LdtkLoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LdtkLoadWarning::SkippedLevel(e) => {
+                write!(f, "skipped level that failed to load: {}", e)
+            }
+            LdtkLoadWarning::UnknownField(e) => {
+                write!(
+                    f,
+                    "kept field that failed to load as FieldValue::Unknown: {}",
+                    e
+                )
+            }
+        }
+    }
+}
+
+/// Parses an LDtk project, pinpointing the offending value if it fails.
+///
+/// This is the same parser used by the [LdtkLoader].
+///
+/// When `lenient`, the project is parsed with [parse_ldtk_json_leniently], and its warnings are
+/// logged.
+pub fn parse_ldtk_json(bytes: &[u8], lenient: bool) -> Result<LdtkJson, Box<LdtkLoadError>> {
+    if lenient {
+        let (project, warnings) = parse_ldtk_json_leniently(bytes)?;
+        log_warnings(&warnings);
+        Ok(project)
+    } else {
+        let locate = |value: &Value| locate_project_error(value, LdtkErrorLocation::root());
+        serde_json::from_slice(bytes).map_err(|e| diagnose(bytes, e, locate))
+    }
+}
+
+/// Parses an LDtk project, skipping levels that fail to parse and keeping fields that can't be
+/// re-typed as [FieldValue::Unknown].
+///
+/// Returns a warning for each level skipped and field kept.
+pub fn parse_ldtk_json_leniently(
+    bytes: &[u8],
+) -> Result<(LdtkJson, Vec<LdtkLoadWarning>), Box<LdtkLoadError>> {
+    let locate = |value: &Value| locate_project_error(value, LdtkErrorLocation::root());
+
+    deserialize_leniently(|| {
+        let mut value = parse_value(bytes)?;
+        let root = LdtkErrorLocation::root();
+        let mut warnings = Vec::new();
+
+        for (level, location) in array_elements(&value, "levels", &root) {
+            field_warnings(level, location, &mut warnings);
+        }
+
+        if let Some(levels) = value.get_mut("levels").and_then(Value::as_array_mut) {
+            let mut i = 0;
+            levels.retain(|level| {
+                let location = root.index("levels", i);
+                i += 1;
+
+                match check::<Level>(
+                    level,
+                    &LdtkErrorLocation {
+                        level: identifier(level, "identifier"),
+                        ..location
+                    },
+                ) {
+                    Some(error) => {
+                        warnings.push(LdtkLoadWarning::SkippedLevel(error));
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+
+        let project =
+            LdtkJson::deserialize(&value).map_err(|e| diagnose_value(&value, e, locate))?;
+
+        Ok((project, warnings))
+    })
+}
+
+/// Parses an external LDtk level, pinpointing the offending value if it fails.
+///
+/// This is the same parser used by the [LdtkLevelLoader].
+///
+/// When `lenient`, the level is parsed with [parse_level_leniently], and its warnings are logged.
+pub fn parse_level(bytes: &[u8], lenient: bool) -> Result<Level, Box<LdtkLoadError>> {
+    if lenient {
+        let (level, warnings) = parse_level_leniently(bytes)?;
+        log_warnings(&warnings);
+        Ok(level)
+    } else {
+        let locate = |value: &Value| locate_level_error(value, LdtkErrorLocation::root());
+        serde_json::from_slice(bytes).map_err(|e| diagnose(bytes, e, locate))
+    }
+}
+
+/// Parses an external LDtk level, keeping fields that can't be re-typed as
+/// [FieldValue::Unknown].
+///
+/// Returns a warning for each field kept.
+pub fn parse_level_leniently(
+    bytes: &[u8],
+) -> Result<(Level, Vec<LdtkLoadWarning>), Box<LdtkLoadError>> {
+    let locate = |value: &Value| locate_level_error(value, LdtkErrorLocation::root());
+
+    let value = parse_value(bytes)?;
+    let mut warnings = Vec::new();
+
+    field_warnings(&value, LdtkErrorLocation::root(), &mut warnings);
+
+    let level = deserialize_leniently(|| Level::deserialize(&value))
+        .map_err(|e| diagnose_value(&value, e, locate))?;
+
+    Ok((level, warnings))
+}
+
+fn log_warnings(warnings: &[LdtkLoadWarning]) {
+    for warning in warnings {
+        warn!("{}", warning);
+    }
+}
+
+fn parse_value(bytes: &[u8]) -> Result<Value, Box<LdtkLoadError>> {
+    serde_json::from_slice(bytes).map_err(|e| Box::new(LdtkLoadError::Syntax(e)))
+}
+
+/// Reparses the file as a [Value] to find where the original error occurred.
+///
+/// This only happens once loading has already failed, so it doesn't slow down valid files.
+fn diagnose(
+    bytes: &[u8],
+    error: serde_json::Error,
+    locate: impl Fn(&Value) -> Option<LdtkLoadError>,
+) -> Box<LdtkLoadError> {
+    match parse_value(bytes) {
+        Ok(value) => diagnose_value(&value, error, locate),
+        Err(e) => e,
+    }
+}
+
+fn diagnose_value(
+    value: &Value,
+    error: serde_json::Error,
+    locate: impl Fn(&Value) -> Option<LdtkLoadError>,
+) -> Box<LdtkLoadError> {
+    Box::new(locate(value).unwrap_or(LdtkLoadError::Structure {
+        location: LdtkErrorLocation::root(),
+        source: error,
+    }))
+}
+
+/// Adds a warning for every field instance in the level that can't be re-typed.
+fn field_warnings(
+    level: &Value,
+    mut location: LdtkErrorLocation,
+    warnings: &mut Vec<LdtkLoadWarning>,
+) {
+    location.level = identifier(level, "identifier");
+
+    let mut warn_fields = |owner: &Value, location: &LdtkErrorLocation| {
+        for (field, location) in array_elements(owner, "fieldInstances", location) {
+            if let Some(error) = locate_field_error(field, location) {
+                warnings.push(LdtkLoadWarning::UnknownField(error));
+            }
+        }
+    };
+
+    warn_fields(level, &location);
+
+    for (layer, location) in array_elements(level, "layerInstances", &location) {
+        for (entity, mut location) in array_elements(layer, "entityInstances", &location) {
+            location.entity = identifier(entity, "__identifier");
+            warn_fields(entity, &location);
+        }
+    }
+}
+
+impl LdtkErrorLocation {
+    pub(crate) fn root() -> Self {
+        LdtkErrorLocation {
+            json_path: "$".to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn child(&self, key: &str) -> Self {
+        LdtkErrorLocation {
+            json_path: format!("{}.{}", self.json_path, key),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn index(&self, key: &str, index: usize) -> Self {
+        LdtkErrorLocation {
+            json_path: format!("{}.{}[{}]", self.json_path, key, index),
+            ..self.clone()
+        }
+    }
+}
+
+fn identifier(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// Returns each element of the array at `key`, along with its location.
+fn array_elements<'a>(
+    value: &'a Value,
+    key: &'a str,
+    location: &'a LdtkErrorLocation,
+) -> impl Iterator<Item = (&'a Value, LdtkErrorLocation)> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(move |(i, element)| (element, location.index(key, i)))
+}
+
+fn check<T: DeserializeOwned>(
+    value: &Value,
+    location: &LdtkErrorLocation,
+) -> Option<LdtkLoadError> {
+    T::deserialize(value)
+        .err()
+        .map(|source| LdtkLoadError::Structure {
+            location: location.clone(),
+            source,
+        })
+}
+
+fn locate_project_error(project: &Value, location: LdtkErrorLocation) -> Option<LdtkLoadError> {
+    array_elements(project, "levels", &location)
+        .find_map(|(level, location)| locate_level_error(level, location))
+        .or_else(|| {
+            project
+                .get("defs")
+                .and_then(|defs| check::<Definitions>(defs, &location.child("defs")))
+        })
+}
+
+fn locate_level_error(level: &Value, mut location: LdtkErrorLocation) -> Option<LdtkLoadError> {
+    location.level = identifier(level, "identifier");
+
+    array_elements(level, "fieldInstances", &location)
+        .find_map(|(field, location)| locate_field_error(field, location))
+        .or_else(|| {
+            array_elements(level, "layerInstances", &location)
+                .find_map(|(layer, location)| locate_layer_error(layer, location))
+        })
+        .or_else(|| check::<Level>(level, &location))
+}
+
+fn locate_layer_error(layer: &Value, location: LdtkErrorLocation) -> Option<LdtkLoadError> {
+    array_elements(layer, "entityInstances", &location)
+        .find_map(|(entity, location)| locate_entity_error(entity, location))
+        .or_else(|| check::<LayerInstance>(layer, &location))
+}
+
+fn locate_entity_error(entity: &Value, mut location: LdtkErrorLocation) -> Option<LdtkLoadError> {
+    location.entity = identifier(entity, "__identifier");
+
+    array_elements(entity, "fieldInstances", &location)
+        .find_map(|(field, location)| locate_field_error(field, location))
+        .or_else(|| check::<EntityInstance>(entity, &location))
+}
+
+fn locate_field_error(field: &Value, mut location: LdtkErrorLocation) -> Option<LdtkLoadError> {
+    location.field = identifier(field, "__identifier");
+
+    if let (Some(field_type), Some(value)) = (identifier(field, "__type"), field.get("__value")) {
+        match field_value_from_json(&field_type, value) {
+            Ok(_) => (),
+            Err(FieldValueError::UnknownType) => {
+                return Some(LdtkLoadError::UnknownFieldType {
+                    location,
+                    field_type,
+                })
+            }
+            Err(FieldValueError::InvalidValue(source)) => {
+                return Some(LdtkLoadError::InvalidFieldValue {
+                    location: location.child("__value"),
+                    field_type,
+                    source,
+                })
+            }
+        }
+    }
+
+    check::<FieldInstance>(field, &location)
+}
 
 fn ldtk_path_to_asset_path<'a, 'b>(
     load_context: &LoadContext<'a>,
@@ -33,6 +463,9 @@ pub type TilesetMap = HashMap<i32, Handle<Image>>;
 /// Used in [LdtkAsset]. Key is the level uid.
 pub type LevelMap = HashMap<i32, Handle<LdtkLevel>>;
 
+/// Used in [LdtkAsset]. Key is the level uid, value is the asset path of the external level file.
+pub type LevelPathMap = HashMap<i32, PathBuf>;
+
 /// Main asset for loading ldtk files.
 ///
 /// Load your ldtk project with the asset server, then insert the handle into the
@@ -42,7 +475,13 @@ pub type LevelMap = HashMap<i32, Handle<LdtkLevel>>;
 pub struct LdtkAsset {
     pub project: LdtkJson,
     pub tileset_map: TilesetMap,
+    /// Handles to the levels of the project.
+    ///
+    /// When external levels are loaded lazily, this is empty, and levels are loaded from
+    /// [LdtkAsset::level_paths] when they are spawned instead.
     pub level_map: LevelMap,
+    /// Paths to the external level files of the project, if it uses external levels.
+    pub level_paths: LevelPathMap,
 }
 
 impl LdtkAsset {
@@ -63,10 +502,105 @@ impl LdtkAsset {
             .find(|(i, l)| level_selection.is_match(i, l))
             .map(|(_, l)| l)
     }
+
+    /// Returns true if the project has a level with the given uid, whether or not it is loaded.
+    pub fn has_level(&self, level_uid: i32) -> bool {
+        self.level_map.contains_key(&level_uid) || self.level_paths.contains_key(&level_uid)
+    }
+
+    /// Returns a handle to the level with the given uid.
+    ///
+    /// If the level is external and hasn't been loaded yet, it starts loading.
+    /// The level stays loaded only as long as a strong handle to it exists.
+    pub fn get_level_handle(
+        &self,
+        level_uid: i32,
+        asset_server: &AssetServer,
+    ) -> Option<Handle<LdtkLevel>> {
+        match self.level_map.get(&level_uid) {
+            Some(level_handle) => Some(level_handle.clone()),
+            None => self
+                .level_paths
+                .get(&level_uid)
+                .map(|path| asset_server.load(path.as_path())),
+        }
+    }
+
+    /// Adds a level created at runtime to the project, replacing any level with the same uid.
+    ///
+    /// The level is stored in the project like the levels of a standalone project, even if the
+    /// project uses external levels.
+    /// It can then be spawned like any other level, by adding its uid to a [LevelSet].
+    /// If a level with the same uid is already spawned, it is respawned with the new content.
+    ///
+    /// Levels can be created with a [LevelBuilder](crate::generation::LevelBuilder).
+    pub fn insert_level(
+        &mut self,
+        level: Level,
+        level_assets: &mut Assets<LdtkLevel>,
+    ) -> Handle<LdtkLevel> {
+        let level = Level {
+            external_rel_path: None,
+            ..level
+        };
+
+        self.level_paths.remove(&level.uid);
+
+        match self.project.levels.iter_mut().find(|l| l.uid == level.uid) {
+            Some(existing) => *existing = level.clone(),
+            None => self.project.levels.push(level.clone()),
+        }
+
+        let level_uid = level.uid;
+        let level_handle = level_assets.add(LdtkLevel { level });
+        self.level_map.insert(level_uid, level_handle.clone());
+
+        level_handle
+    }
+}
+
+/// [AssetLoader] for [LdtkAsset]s.
+///
+/// The plugin configures this loader with [LdtkSettings::external_level_loading] and
+/// [LdtkSettings::lenient_loading] when it is added, so those settings must be inserted before the
+/// plugin to take effect.
+///
+/// In debug builds, projects are also checked with [validate], logging any problems found.
+#[derive(Clone, Debug, Default)]
+pub struct LdtkLoader {
+    pub external_level_loading: ExternalLevelLoading,
+    pub lenient_loading: bool,
+    pub(crate) load_errors: LdtkLoadErrorQueue,
+    pub(crate) external_level_projects: ExternalLevelProjects,
 }
 
-#[derive(Copy, Clone, Debug, Default)]
-pub struct LdtkLoader;
+/// The projects of the external levels loaded by the [LdtkLoader], by level asset path.
+///
+/// Shared with the [LdtkLevelLoader] in debug builds, so external levels can be validated against
+/// their project without parsing it again for every level.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ExternalLevelProjects {
+    projects: Arc<Mutex<HashMap<PathBuf, Arc<LdtkJson>>>>,
+}
+
+impl ExternalLevelProjects {
+    fn insert(&self, level_paths: impl IntoIterator<Item = PathBuf>, project: LdtkJson) {
+        let project = Arc::new(project);
+
+        if let Ok(mut projects) = self.projects.lock() {
+            for level_path in level_paths {
+                projects.insert(level_path, project.clone());
+            }
+        }
+    }
+
+    fn get(&self, level_path: &Path) -> Option<Arc<LdtkJson>> {
+        self.projects
+            .lock()
+            .ok()
+            .and_then(|projects| projects.get(level_path).cloned())
+    }
+}
 
 impl AssetLoader for LdtkLoader {
     fn load<'a>(
@@ -75,17 +609,37 @@ impl AssetLoader for LdtkLoader {
         load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, anyhow::Result<()>> {
         Box::pin(async move {
-            let project: LdtkJson = serde_json::from_slice(bytes)?;
+            let project = match parse_ldtk_json(bytes, self.lenient_loading) {
+                Ok(project) => project,
+                Err(error) => return Err(self.load_errors.report(load_context, *error)),
+            };
+
+            if cfg!(debug_assertions) {
+                for diagnostic in validate(&project) {
+                    let path = load_context.path().display();
+                    match diagnostic.severity() {
+                        Severity::Warning => warn!("{}: {}", path, diagnostic),
+                        Severity::Error => error!("{}: {}", path, diagnostic),
+                    }
+                }
+            }
 
             let mut external_level_paths = Vec::new();
             let mut level_map = HashMap::new();
+            let mut level_paths = HashMap::new();
             if project.external_levels {
                 for level in &project.levels {
                     if let Some(external_rel_path) = &level.external_rel_path {
                         let asset_path = ldtk_path_to_asset_path(load_context, external_rel_path);
 
-                        external_level_paths.push(asset_path.clone());
-                        level_map.insert(level.uid, load_context.get_handle(asset_path));
+                        level_paths.insert(level.uid, asset_path.path().to_path_buf());
+
+                        // Lazily loaded levels are not dependencies, and no handles to them are
+                        // kept, so they can be unloaded when their level entities despawn.
+                        if self.external_level_loading == ExternalLevelLoading::Eager {
+                            external_level_paths.push(asset_path.clone());
+                            level_map.insert(level.uid, load_context.get_handle(asset_path));
+                        }
                     }
                 }
             } else {
@@ -101,6 +655,11 @@ impl AssetLoader for LdtkLoader {
                 }
             }
 
+            if cfg!(debug_assertions) && !level_paths.is_empty() {
+                self.external_level_projects
+                    .insert(level_paths.values().cloned(), project.clone());
+            }
+
             let mut tileset_rel_paths = Vec::new();
             let mut tileset_map = HashMap::new();
             for tileset in &project.defs.tilesets {
@@ -114,6 +673,7 @@ impl AssetLoader for LdtkLoader {
                 project,
                 tileset_map,
                 level_map,
+                level_paths,
             };
             load_context.set_default_asset(
                 LoadedAsset::new(ldtk_asset)
@@ -141,8 +701,31 @@ pub struct LdtkLevel {
     pub level: Level,
 }
 
-#[derive(Copy, Clone, Debug, Default)]
-pub struct LdtkLevelLoader;
+impl LdtkLevel {
+    /// Returns true if any of this level's layers or entities draw from the given tileset.
+    pub fn uses_tileset(&self, tileset_uid: i32) -> bool {
+        self.level.layer_instances.iter().flatten().any(|layer| {
+            layer.tileset_def_uid == Some(tileset_uid)
+                || layer.override_tileset_uid == Some(tileset_uid)
+                || layer
+                    .entity_instances
+                    .iter()
+                    .filter_map(|e| e.tile.as_ref())
+                    .any(|t| t.tileset_uid == tileset_uid)
+        })
+    }
+}
+
+/// [AssetLoader] for the [LdtkLevel]s of projects with external levels.
+///
+/// In debug builds, levels are also checked with [validate_level] against the project they were
+/// loaded for by the [LdtkLoader], logging any problems found.
+#[derive(Clone, Debug, Default)]
+pub struct LdtkLevelLoader {
+    pub lenient_loading: bool,
+    pub(crate) load_errors: LdtkLoadErrorQueue,
+    pub(crate) external_level_projects: ExternalLevelProjects,
+}
 
 impl AssetLoader for LdtkLevelLoader {
     fn load<'a>(
@@ -151,9 +734,29 @@ impl AssetLoader for LdtkLevelLoader {
         load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, anyhow::Result<()>> {
         Box::pin(async move {
-            let ldtk_level = LdtkLevel {
-                level: serde_json::from_slice(bytes)?,
+            let ldtk_level = match parse_level(bytes, self.lenient_loading) {
+                Ok(level) => LdtkLevel { level },
+                Err(error) => return Err(self.load_errors.report(load_context, *error)),
             };
+
+            if cfg!(debug_assertions) {
+                match self.external_level_projects.get(load_context.path()) {
+                    Some(project) => {
+                        for diagnostic in validate_level(&project, &ldtk_level.level) {
+                            let path = load_context.path().display();
+                            match diagnostic.severity() {
+                                Severity::Warning => warn!("{}: {}", path, diagnostic),
+                                Severity::Error => error!("{}: {}", path, diagnostic),
+                            }
+                        }
+                    }
+                    None => debug!(
+                        "No project has been loaded for {}, so it isn't validated",
+                        load_context.path().display()
+                    ),
+                }
+            }
+
             load_context.set_default_asset(LoadedAsset::new(ldtk_level));
             Ok(())
         })
@@ -163,3 +766,174 @@ impl AssetLoader for LdtkLevelLoader {
         &["ldtkl"]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLATFORMER: &str = include_str!("../assets/Typical_2D_platformer_example.ldtk");
+
+    /// Returns the project with the first entity field instance of type `Array<Point>` altered.
+    fn break_first_points_field(alter: impl FnOnce(&mut Value)) -> Vec<u8> {
+        let mut project: Value = serde_json::from_str(PLATFORMER).unwrap();
+
+        let field = project["levels"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .flat_map(|l| l["layerInstances"].as_array_mut().unwrap().iter_mut())
+            .flat_map(|l| l["entityInstances"].as_array_mut().unwrap().iter_mut())
+            .flat_map(|e| e["fieldInstances"].as_array_mut().unwrap().iter_mut())
+            .find(|f| f["__type"] == "Array<Point>")
+            .unwrap();
+
+        alter(field);
+
+        serde_json::to_vec(&project).unwrap()
+    }
+
+    #[test]
+    fn test_valid_project_parses() {
+        assert!(parse_ldtk_json(PLATFORMER.as_bytes(), false).is_ok());
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let error = parse_ldtk_json(b"{\"levels\": [", false).unwrap_err();
+
+        assert!(matches!(*error, LdtkLoadError::Syntax(_)));
+        assert_eq!(error.location(), None);
+    }
+
+    #[test]
+    fn test_unknown_field_type() {
+        let bytes = break_first_points_field(|f| f["__type"] = Value::from("Array<Wormhole>"));
+        let error = parse_ldtk_json(&bytes, false).unwrap_err();
+
+        match *error {
+            LdtkLoadError::UnknownFieldType {
+                location,
+                field_type,
+            } => {
+                assert_eq!(field_type, "Array<Wormhole>");
+                assert_eq!(
+                    location.level,
+                    Some("Your_typical_2D_platformer".to_string())
+                );
+                assert_eq!(location.entity, Some("Mob".to_string()));
+                assert_eq!(location.field, Some("patrol".to_string()));
+                assert!(location
+                    .json_path
+                    .starts_with("$.levels[0].layerInstances["));
+                assert!(location.json_path.contains("].fieldInstances["));
+            }
+            e => panic!("expected UnknownFieldType, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_invalid_field_value() {
+        let bytes = break_first_points_field(|f| f["__value"] = Value::from("nowhere"));
+        let error = parse_ldtk_json(&bytes, false).unwrap_err();
+
+        match *error {
+            LdtkLoadError::InvalidFieldValue {
+                location,
+                field_type,
+                ..
+            } => {
+                assert_eq!(field_type, "Array<Point>");
+                assert_eq!(location.field, Some("patrol".to_string()));
+                assert!(location.json_path.ends_with("].__value"));
+            }
+            e => panic!("expected InvalidFieldValue, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_structure_error() {
+        let mut project: Value = serde_json::from_str(PLATFORMER).unwrap();
+        project["levels"][1]["pxWid"] = Value::from("wide");
+
+        let error = parse_ldtk_json(&serde_json::to_vec(&project).unwrap(), false).unwrap_err();
+
+        match *error {
+            LdtkLoadError::Structure { location, .. } => {
+                assert_eq!(location.json_path, "$.levels[1]");
+                assert_eq!(location.level, Some("Top".to_string()));
+            }
+            e => panic!("expected Structure, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_lenient_unknown_field_type() {
+        let bytes = break_first_points_field(|f| f["__type"] = Value::from("Array<Wormhole>"));
+        let (project, warnings) = parse_ldtk_json_leniently(&bytes).unwrap();
+
+        assert!(matches!(
+            warnings[..],
+            [LdtkLoadWarning::UnknownField(
+                LdtkLoadError::UnknownFieldType { .. }
+            )]
+        ));
+
+        let field = project
+            .levels
+            .iter()
+            .flat_map(|l| l.layer_instances.iter().flatten())
+            .flat_map(|l| &l.entity_instances)
+            .flat_map(|e| &e.field_instances)
+            .find(|f| f.field_instance_type == "Array<Wormhole>")
+            .unwrap();
+
+        assert!(matches!(field.value, FieldValue::Unknown(Value::Array(_))));
+    }
+
+    #[test]
+    fn test_external_level_projects() {
+        let external_level_projects = ExternalLevelProjects::default();
+        let level_paths = ["world/Level_0.ldtkl", "world/Level_1.ldtkl"].map(PathBuf::from);
+
+        let mut project: LdtkJson = serde_json::from_str(PLATFORMER).unwrap();
+        external_level_projects.insert(level_paths.clone(), project.clone());
+
+        // Levels share the project, which is only parsed once
+        let (level_0, level_1) = (
+            external_level_projects.get(&level_paths[0]).unwrap(),
+            external_level_projects.get(&level_paths[1]).unwrap(),
+        );
+        assert!(Arc::ptr_eq(&level_0, &level_1));
+        assert_eq!(*level_0, project);
+        assert!(external_level_projects
+            .get(Path::new("world/Level_2.ldtkl"))
+            .is_none());
+
+        // Reloading the project replaces it
+        project.json_version = "1.0.0".to_string();
+        external_level_projects.insert(level_paths.clone(), project.clone());
+        assert_eq!(
+            *external_level_projects.get(&level_paths[1]).unwrap(),
+            project
+        );
+    }
+
+    #[test]
+    fn test_lenient_skips_broken_levels() {
+        let mut project: Value = serde_json::from_str(PLATFORMER).unwrap();
+        let level_count = project["levels"].as_array().unwrap().len();
+        project["levels"][1]["pxWid"] = Value::from("wide");
+
+        let (project, warnings) =
+            parse_ldtk_json_leniently(&serde_json::to_vec(&project).unwrap()).unwrap();
+
+        assert!(matches!(
+            warnings[..],
+            [LdtkLoadWarning::SkippedLevel(
+                LdtkLoadError::Structure { .. }
+            )]
+        ));
+        assert_eq!(project.levels.len(), level_count - 1);
+        assert!(project.levels.iter().all(|l| l.identifier != "Top"));
+    }
+}
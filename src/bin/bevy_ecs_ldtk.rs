@@ -0,0 +1,254 @@
+//! Inspects and validates LDtk projects using the same parser as the plugin.
+//!
+//! Requires the "cli" feature:
+//! ```sh
+//! cargo run --features cli -- assets/my_project.ldtk
+//! ```
+//!
+//! Exits with a nonzero status if any project fails to load or has validation errors, making it
+//! suitable for catching broken level files in CI.
+
+use bevy_ecs_ldtk::{
+    ldtk::{LdtkJson, Level},
+    parse_ldtk_json, parse_ldtk_json_leniently, parse_level, parse_level_leniently,
+    validation::{validate, validate_level, Diagnostic, Severity},
+    LdtkLoadError, LdtkLoadWarning,
+};
+use std::{env, fs, path::Path, process};
+
+const USAGE: &str = "\
+Usage: bevy_ecs_ldtk [OPTIONS] <PROJECT.ldtk>...
+
+Options:
+    --lenient   Skip broken levels and keep unknown fields instead of failing
+    --quiet     Only print diagnostics, not the contents of the projects
+    --help      Print this message";
+
+struct Options {
+    lenient: bool,
+    quiet: bool,
+    paths: Vec<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        lenient: false,
+        quiet: false,
+        paths: Vec::new(),
+    };
+
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--lenient" => options.lenient = true,
+            "--quiet" => options.quiet = true,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            a if a.starts_with('-') => return Err(format!("unknown option {}", a)),
+            _ => options.paths.push(arg),
+        }
+    }
+
+    if options.paths.is_empty() {
+        return Err("no project given".to_string());
+    }
+
+    Ok(options)
+}
+
+fn print_project(project: &LdtkJson, levels: &[Level]) {
+    println!("  LDtk version {}", project.json_version);
+
+    println!("  Levels ({}):", levels.len());
+    for level in levels {
+        println!(
+            "    {} (uid {}, {}x{} px at {}, {})",
+            level.identifier, level.uid, level.px_wid, level.px_hei, level.world_x, level.world_y
+        );
+
+        for layer in level.layer_instances.iter().flatten() {
+            println!(
+                "      {} ({:?}, {}x{} cells, {} entities)",
+                layer.identifier,
+                layer.layer_instance_type,
+                layer.c_wid,
+                layer.c_hei,
+                layer.entity_instances.len()
+            );
+        }
+    }
+
+    println!("  Entity definitions ({}):", project.defs.entities.len());
+    for entity in &project.defs.entities {
+        println!(
+            "    {} (uid {}, {}x{} px)",
+            entity.identifier, entity.uid, entity.width, entity.height
+        );
+
+        for field in &entity.field_defs {
+            println!(
+                "      {}: {}",
+                field.identifier, field.field_definition_type
+            );
+        }
+    }
+
+    println!("  Enums ({}):", project.defs.enums.len());
+    for enum_definition in &project.defs.enums {
+        let values: Vec<&str> = enum_definition
+            .values
+            .iter()
+            .map(|v| v.id.as_str())
+            .collect();
+
+        println!(
+            "    {} (uid {}): {}",
+            enum_definition.identifier,
+            enum_definition.uid,
+            values.join(", ")
+        );
+    }
+
+    println!("  Tilesets ({}):", project.defs.tilesets.len());
+    for tileset in &project.defs.tilesets {
+        println!(
+            "    {} (uid {}, {}, {} px grid)",
+            tileset.identifier, tileset.uid, tileset.rel_path, tileset.tile_grid_size
+        );
+    }
+}
+
+/// Parses a file with the strict parser, or the lenient one if `lenient`, returning the warnings
+/// of the latter.
+fn parse<T>(
+    path: &Path,
+    lenient: bool,
+    strict_parser: impl Fn(&[u8], bool) -> Result<T, Box<LdtkLoadError>>,
+    lenient_parser: impl Fn(&[u8]) -> Result<(T, Vec<LdtkLoadWarning>), Box<LdtkLoadError>>,
+) -> Result<(T, Vec<LdtkLoadWarning>), String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+
+    if lenient {
+        lenient_parser(&bytes).map_err(|e| e.to_string())
+    } else {
+        strict_parser(&bytes, false)
+            .map(|parsed| (parsed, Vec::new()))
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Loads, prints, and validates a project, returning whether it is free of errors.
+fn inspect(path: &Path, options: &Options) -> bool {
+    println!("{}", path.display());
+
+    let mut warnings: Vec<(String, LdtkLoadWarning)> = Vec::new();
+
+    let project = match parse(
+        path,
+        options.lenient,
+        parse_ldtk_json,
+        parse_ldtk_json_leniently,
+    ) {
+        Ok((project, project_warnings)) => {
+            warnings.extend(
+                project_warnings
+                    .into_iter()
+                    .map(|w| (path.display().to_string(), w)),
+            );
+            project
+        }
+        Err(e) => {
+            println!("  error: {}", e);
+            return false;
+        }
+    };
+
+    let mut ok = true;
+    let mut diagnostics: Vec<(String, Diagnostic)> = validate(&project)
+        .into_iter()
+        .map(|d| (path.display().to_string(), d))
+        .collect();
+
+    // External levels are loaded from their own files, relative to the project.
+    let mut levels = Vec::new();
+    for level in &project.levels {
+        match &level.external_rel_path {
+            Some(rel_path) if project.external_levels => {
+                let level_path = path
+                    .parent()
+                    .unwrap_or_else(|| Path::new(""))
+                    .join(rel_path);
+
+                match parse(
+                    &level_path,
+                    options.lenient,
+                    parse_level,
+                    parse_level_leniently,
+                ) {
+                    Ok((external_level, level_warnings)) => {
+                        warnings.extend(
+                            level_warnings
+                                .into_iter()
+                                .map(|w| (level_path.display().to_string(), w)),
+                        );
+                        diagnostics.extend(
+                            validate_level(&project, &external_level)
+                                .into_iter()
+                                .map(|d| (level_path.display().to_string(), d)),
+                        );
+                        levels.push(external_level);
+                    }
+                    Err(e) => {
+                        println!("  error: {}: {}", level_path.display(), e);
+                        ok = false;
+                        levels.push(level.clone());
+                    }
+                }
+            }
+            _ => levels.push(level.clone()),
+        }
+    }
+
+    if !options.quiet {
+        print_project(&project, &levels);
+    }
+
+    // Lenient parsing recovers from these, so they aren't errors.
+    for (file, warning) in &warnings {
+        println!("  warning: {}: {}", file, warning);
+    }
+
+    for (file, diagnostic) in &diagnostics {
+        let severity = match diagnostic.severity() {
+            Severity::Warning => "warning",
+            Severity::Error => {
+                ok = false;
+                "error"
+            }
+        };
+
+        println!("  {}: {}: {}", severity, file, diagnostic);
+    }
+
+    ok
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let mut ok = true;
+    for path in &options.paths {
+        ok &= inspect(Path::new(path), &options);
+    }
+
+    if !ok {
+        process::exit(1);
+    }
+}
@@ -1,7 +1,8 @@
 //! [Component]s and [Bundle]s used by the plugin.
 
+use crate::ldtk::Type;
 pub use crate::ldtk::{EntityInstance, LayerInstance};
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 
 use std::{
     collections::HashSet,
@@ -12,7 +13,7 @@ use std::{
 use crate::{
     assets::LdtkLevel,
     prelude::{LdtkEntity, LdtkIntCell},
-    resources::{LdtkSettings, LevelSelection},
+    resources::{IntGridStorage, LdtkSettings, LdtkWorldEvent, LevelSelection},
     utils::ldtk_grid_coords_to_grid_coords,
 };
 
@@ -45,9 +46,51 @@ pub struct LevelSet {
     pub uids: HashSet<i32>,
 }
 
+/// [Component] added to level entities, storing the `uid` of the level in LDtk.
+///
+/// Level entities are spawned as children of the [LdtkWorldBundle] according to its [LevelSet].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LevelUid(pub i32);
+
+/// [Component] on the [LdtkWorldBundle] reporting how far along the world is in loading and
+/// spawning the levels of its [LevelSet].
+///
+/// This is updated by the plugin every update, after [GlobalTransform]s are propagated.
+/// When it becomes ready, an [LdtkWorldEvent::Ready] event is fired.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct LdtkLoadProgress {
+    /// The LDtk project file has loaded and been parsed.
+    pub project_loaded: bool,
+    /// The number of the project's tileset images that have loaded.
+    pub tilesets_loaded: usize,
+    /// The number of tileset images used by the project.
+    pub tilesets_total: usize,
+    /// The number of levels in the [LevelSet] whose level data has loaded.
+    pub levels_loaded: usize,
+    /// The number of levels in the [LevelSet] that have fully spawned.
+    pub levels_spawned: usize,
+    /// The number of levels in the [LevelSet].
+    ///
+    /// Once the project has loaded, uids of levels that it doesn't have are left out, with a
+    /// warning, so they don't keep the world from becoming ready.
+    pub levels_total: usize,
+}
+
+impl LdtkLoadProgress {
+    /// Returns true if the project, its tilesets, and every level in the [LevelSet] that the
+    /// project has have loaded, and those levels have spawned.
+    pub fn is_ready(&self) -> bool {
+        self.project_loaded
+            && self.tilesets_loaded == self.tilesets_total
+            && self.levels_loaded == self.levels_total
+            && self.levels_spawned == self.levels_total
+    }
+}
+
 /// [Component] that indicates that an ldtk entity should be a child of the world, not the level.
 ///
-/// By default, [LdtkEntity]s are children of the level they spawn in.
+/// By default, [LdtkEntity]s are children of the layer they spawn in, which is a child of its
+/// level.
 /// This can be a problem if that entity is supposed to travel across multiple levels, since they
 /// will despawn the moment the level they were born in despawns.
 ///
@@ -87,6 +130,178 @@ impl Worldly {
     }
 }
 
+/// [Component] added to the entities spawned for each LDtk layer.
+///
+/// Tile, AutoTile, and IntGrid layers may be spawned as several bevy_ecs_tilemap layers, each of
+/// which has this component.
+/// Entities layers are spawned as a single entity with this component, and the layer's
+/// [EntityInstance]s are spawned as its children.
+/// Layer entities are children of their level.
+///
+/// The `z` value is the translation z used when rendering the layer.
+/// Sprites you spawn with a z between that of two LDtk layers will be drawn between them.
+/// See [LdtkSettings] for configuring these values, and [LdtkLayerQuery] for looking them up.
+#[derive(Clone, PartialEq, Debug, Component)]
+pub struct LdtkLayer {
+    pub identifier: String,
+    pub layer_def_uid: i32,
+    pub layer_instance_type: Type,
+    pub grid_size: i32,
+    pub px_total_offset: IVec2,
+    pub z: f32,
+}
+
+impl LdtkLayer {
+    /// Creates an [LdtkLayer] from the given [LayerInstance], rendered at the given z value.
+    pub fn from_layer_instance(layer_instance: &LayerInstance, z: f32) -> LdtkLayer {
+        LdtkLayer {
+            identifier: layer_instance.identifier.clone(),
+            layer_def_uid: layer_instance.layer_def_uid,
+            layer_instance_type: layer_instance.layer_instance_type.clone(),
+            grid_size: layer_instance.grid_size,
+            px_total_offset: IVec2::new(
+                layer_instance.px_total_offset_x,
+                layer_instance.px_total_offset_y,
+            ),
+            z,
+        }
+    }
+}
+
+/// [SystemParam] for looking up the z values of spawned LDtk layers.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// fn place_player(
+///     mut player_query: Query<(&mut Transform, &Parent), With<Sprite>>,
+///     layer_query: LdtkLayerQuery,
+/// ) {
+///     for (mut transform, parent) in player_query.iter_mut() {
+///         if let Some(z) = layer_query.layer_z(parent.0, "Foreground") {
+///             transform.translation.z = z - 0.5;
+///         }
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct LdtkLayerQuery<'w, 's> {
+    layer_query: Query<'w, 's, (Entity, &'static LdtkLayer, &'static Parent)>,
+}
+
+impl<'w, 's> LdtkLayerQuery<'w, 's> {
+    /// Returns the entities spawned for the layer with the given identifier in the given level.
+    ///
+    /// There may be several of these for Tile, AutoTile, and IntGrid layers.
+    pub fn layer_entities<'a>(
+        &'a self,
+        level_entity: Entity,
+        identifier: &'a str,
+    ) -> impl Iterator<Item = (Entity, &'a LdtkLayer)> + 'a {
+        self.layer_query
+            .iter()
+            .filter(move |(_, layer, parent)| {
+                parent.0 == level_entity && layer.identifier == identifier
+            })
+            .map(|(entity, layer, _)| (entity, layer))
+    }
+
+    /// Returns the z value of the layer with the given identifier in the given level.
+    ///
+    /// If the layer was spawned as several bevy_ecs_tilemap layers, this is the lowest of their z
+    /// values.
+    /// So, anything drawn just beneath this value is drawn above all the layers beneath it.
+    pub fn layer_z(&self, level_entity: Entity, identifier: &str) -> Option<f32> {
+        self.layer_entities(level_entity, identifier)
+            .map(|(_, layer)| layer.z)
+            .reduce(f32::min)
+    }
+}
+
+/// [Component] storing the values of an IntGrid layer.
+///
+/// Added to the entity that the layer's [IntGridCell] entities are children of.
+/// This allows looking up the value of any cell by its [GridCoords] without querying for cell
+/// entities, which is especially useful with [IntGridStorage::Compact].
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct IntGrid {
+    width: i32,
+    height: i32,
+    /// Values in LDtk's row-major order, starting from the top-left cell.
+    values: Vec<i32>,
+}
+
+impl IntGrid {
+    /// Creates an [IntGrid] from the `int_grid_csv` of the given [LayerInstance].
+    pub fn from_layer_instance(layer_instance: &LayerInstance) -> IntGrid {
+        IntGrid {
+            width: layer_instance.c_wid,
+            height: layer_instance.c_hei,
+            values: layer_instance.int_grid_csv.clone(),
+        }
+    }
+
+    /// Width of the grid, in cells.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Height of the grid, in cells.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn index(&self, grid_coords: GridCoords) -> Option<usize> {
+        if (0..self.width).contains(&grid_coords.x) && (0..self.height).contains(&grid_coords.y) {
+            Some(((self.height - 1 - grid_coords.y) * self.width + grid_coords.x) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of the cell at the given [GridCoords], or [None] if they are out of
+    /// bounds.
+    ///
+    /// Empty cells have a value of `0`.
+    pub fn get(&self, grid_coords: GridCoords) -> Option<i32> {
+        self.index(grid_coords)
+            .and_then(|i| self.values.get(i))
+            .copied()
+    }
+
+    /// Sets the value of the cell at the given [GridCoords], returning its previous value, or
+    /// [None] if they are out of bounds.
+    ///
+    /// This only changes the stored value.
+    /// Any [IntGridCell] entities or rendered tiles of the cell are left as they are.
+    pub fn set(&mut self, grid_coords: GridCoords, value: i32) -> Option<i32> {
+        self.index(grid_coords)
+            .and_then(|i| self.values.get_mut(i))
+            .map(|cell| std::mem::replace(cell, value))
+    }
+
+    /// The values of the grid in LDtk's `int_grid_csv` format.
+    pub fn int_grid_csv(&self) -> &[i32] {
+        &self.values
+    }
+
+    /// Iterates over the nonzero cells of the grid, along with their [GridCoords].
+    pub fn iter(&self) -> impl Iterator<Item = (GridCoords, i32)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != 0)
+            .map(|(i, value)| {
+                let i = i as i32;
+                let grid_coords = GridCoords {
+                    x: i % self.width,
+                    y: self.height - 1 - i / self.width,
+                };
+                (grid_coords, *value)
+            })
+    }
+}
+
 /// [Component] that stores grid-based coordinate information.
 ///
 /// For Tile, AutoTile, and IntGrid layers, all tiles have this component by default.
@@ -233,6 +448,56 @@ pub(crate) struct EntityInstanceBundle {
 pub struct LdtkWorldBundle {
     pub ldtk_handle: Handle<crate::assets::LdtkAsset>,
     pub level_set: LevelSet,
+    pub load_progress: LdtkLoadProgress,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_int_grid() {
+        let int_grid = IntGrid {
+            width: 3,
+            height: 2,
+            values: vec![1, 0, 2, 0, 3, 0],
+        };
+
+        assert_eq!(int_grid.width(), 3);
+        assert_eq!(int_grid.height(), 2);
+
+        assert_eq!(int_grid.get(GridCoords { x: 0, y: 1 }), Some(1));
+        assert_eq!(int_grid.get(GridCoords { x: 2, y: 1 }), Some(2));
+        assert_eq!(int_grid.get(GridCoords { x: 1, y: 0 }), Some(3));
+        assert_eq!(int_grid.get(GridCoords { x: 0, y: 0 }), Some(0));
+        assert_eq!(int_grid.get(GridCoords { x: 3, y: 0 }), None);
+        assert_eq!(int_grid.get(GridCoords { x: 0, y: 2 }), None);
+        assert_eq!(int_grid.get(GridCoords { x: -1, y: 0 }), None);
+
+        assert_eq!(
+            int_grid.iter().collect::<Vec<_>>(),
+            vec![
+                (GridCoords { x: 0, y: 1 }, 1),
+                (GridCoords { x: 2, y: 1 }, 2),
+                (GridCoords { x: 1, y: 0 }, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_int_grid_set() {
+        let mut int_grid = IntGrid {
+            width: 3,
+            height: 2,
+            values: vec![1, 0, 2, 0, 3, 0],
+        };
+
+        assert_eq!(int_grid.set(GridCoords { x: 1, y: 1 }, 4), Some(0));
+        assert_eq!(int_grid.set(GridCoords { x: 1, y: 0 }, 0), Some(3));
+        assert_eq!(int_grid.set(GridCoords { x: 3, y: 0 }, 5), None);
+
+        assert_eq!(int_grid.int_grid_csv(), &[1, 4, 2, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,1040 @@
+//! An in-game editor for tuning spawned levels during playtests.
+//!
+//! Adding the [LdtkEditorPlugin] to the [App] lets you edit spawned levels with the mouse, while
+//! [LdtkEditorSettings::enabled] is true:
+//! - Holding the left mouse button paints [LdtkEditorSettings::paint_value] onto the IntGrid layer
+//!   named by [LdtkEditorSettings::paint_layer].
+//!   Holding the right mouse button erases cells instead.
+//! - Pressing the left mouse button on an entity with an [EntityInstance] drags it.
+//!
+//! Painting doesn't update the auto-layer tiles of IntGrid layers, since that requires running
+//! LDtk's auto-layer rules.
+//! So painted cells of layers like the platformer example's "Collisions" look the same, even
+//! though their [IntGridCell](crate::IntGridCell) entities change.
+//!
+//! The [LdtkEditor] resource tracks the cursor, and the edits of each level as a list of
+//! [LevelChange]s against the level as it was before it was first edited.
+//! To keep the edits, export the levels with an [LdtkLevelExporter] and save them with the
+//! functions in [export](crate::export).
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{
+//!     editor::{LdtkEditor, LdtkEditorPlugin, LdtkEditorSettings},
+//!     prelude::*,
+//! };
+//!
+//! fn main() {
+//!     App::new()
+//!         .add_plugins(DefaultPlugins)
+//!         .add_plugin(LdtkPlugin)
+//!         .insert_resource(LdtkEditorSettings {
+//!             paint_layer: Some("Collisions".to_string()),
+//!             paint_value: 1,
+//!             ..Default::default()
+//!         })
+//!         .add_plugin(LdtkEditorPlugin)
+//!         .add_system(log_changes)
+//!         .run();
+//! }
+//!
+//! fn log_changes(input: Res<Input<KeyCode>>, editor: Res<LdtkEditor>) {
+//!     if input.just_pressed(KeyCode::F1) {
+//!         for level_uid in editor.edited_levels() {
+//!             for change in editor.changes(level_uid) {
+//!                 info!("level {}: {:?}", level_uid, change);
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{
+    assets::{LdtkAsset, LdtkLevel},
+    components::{EntityInstance, GridCoords, IntGrid, LdtkLayer, LevelUid, Worldly},
+    export::LdtkLevelExporter,
+    ldtk::{FieldValue, LayerInstance, Level, Type},
+    utils::{ldtk_grid_coords_to_grid_coords, translation_to_grid_coords},
+};
+use bevy::{prelude::*, render::camera::CameraPlugin};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+/// [SystemLabel] used by the [LdtkEditorPlugin] for scheduling its systems.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, SystemLabel)]
+pub enum LdtkEditorSystemLabel {
+    Cursor,
+    Dragging,
+    Painting,
+    Tracking,
+}
+
+/// Adds an in-game editor for painting IntGrid values and dragging entities of spawned levels.
+///
+/// The editor is configured with the [LdtkEditorSettings] resource, which should be inserted
+/// before this plugin to take effect.
+/// See the [module-level documentation](crate::editor) for more details.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LdtkEditorPlugin;
+
+impl Plugin for LdtkEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LdtkEditorSettings>()
+            .init_resource::<LdtkEditor>()
+            .add_system(update_editor_cursor.label(LdtkEditorSystemLabel::Cursor))
+            .add_system(
+                drag_ldtk_entities
+                    .label(LdtkEditorSystemLabel::Dragging)
+                    .after(LdtkEditorSystemLabel::Cursor),
+            )
+            .add_system(
+                paint_int_grid
+                    .label(LdtkEditorSystemLabel::Painting)
+                    .after(LdtkEditorSystemLabel::Dragging),
+            )
+            .add_system(
+                track_level_changes
+                    .label(LdtkEditorSystemLabel::Tracking)
+                    .after(LdtkEditorSystemLabel::Painting),
+            )
+            .add_system(respawn_painted_levels.after(LdtkEditorSystemLabel::Tracking));
+    }
+}
+
+/// Resource for configuring the [LdtkEditorPlugin].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct LdtkEditorSettings {
+    /// Whether the editor responds to the mouse.
+    ///
+    /// Defaults to `true`.
+    pub enabled: bool,
+    /// The identifier of the IntGrid layer that is painted on.
+    ///
+    /// No layer is painted on if this is [None], which is the default.
+    pub paint_layer: Option<String>,
+    /// The IntGrid value painted with the left mouse button.
+    ///
+    /// Defaults to `1`.
+    pub paint_value: i32,
+    /// Whether levels are respawned once a paint stroke ends, so that their
+    /// [IntGridCell](crate::IntGridCell) entities reflect the painted values.
+    ///
+    /// The auto-layer tiles of the painted layer are respawned as they were loaded, since they
+    /// aren't generated from IntGrid values.
+    /// Respawning a level also respawns its entities, at their current positions.
+    /// Without respawning, only the level's [IntGrid] components are updated.
+    ///
+    /// Defaults to `true`.
+    pub respawn_painted_levels: bool,
+}
+
+impl Default for LdtkEditorSettings {
+    fn default() -> Self {
+        LdtkEditorSettings {
+            enabled: true,
+            paint_layer: None,
+            paint_value: 1,
+            respawn_painted_levels: true,
+        }
+    }
+}
+
+/// The position of the mouse cursor, as tracked by the [LdtkEditorPlugin].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct EditorCursor {
+    /// The cursor's position in world space.
+    pub world_position: Vec2,
+    /// The spawned level under the cursor, if any.
+    pub level_entity: Option<Entity>,
+    /// The uid of the level under the cursor, if any.
+    pub level_uid: Option<i32>,
+    /// The cell under the cursor in the [LdtkEditorSettings::paint_layer] of the level, if any.
+    ///
+    /// These may be out of the bounds of the layer, near the edges of the level.
+    pub grid_coords: Option<GridCoords>,
+}
+
+/// A difference between a level as it was loaded and its current state.
+///
+/// Entities are identified by their identifier and pixel coordinates, since LDtk doesn't give them
+/// a unique id.
+/// Pixel coordinates are in LDtk's coordinate system, relative to the top-left corner of the level.
+#[derive(Clone, PartialEq, Debug)]
+pub enum LevelChange {
+    IntGridValue {
+        layer: String,
+        grid_coords: GridCoords,
+        from: i32,
+        to: i32,
+    },
+    EntityMoved {
+        layer: String,
+        identifier: String,
+        from: IVec2,
+        to: IVec2,
+    },
+    EntityFieldChanged {
+        layer: String,
+        identifier: String,
+        px: IVec2,
+        field: String,
+        from: FieldValue,
+        to: FieldValue,
+    },
+    EntityAdded {
+        layer: String,
+        identifier: String,
+        px: IVec2,
+    },
+    EntityRemoved {
+        layer: String,
+        identifier: String,
+        px: IVec2,
+    },
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Drag {
+    entity: Entity,
+    level_uid: i32,
+    grab_offset: Vec2,
+}
+
+/// Resource holding the state of the [LdtkEditorPlugin], including the changes made to each
+/// level.
+#[derive(Clone, Debug, Default)]
+pub struct LdtkEditor {
+    cursor: Option<EditorCursor>,
+    drag: Option<Drag>,
+    /// Levels painted on during the current stroke.
+    stroke: HashSet<i32>,
+    /// Levels edited since their changes were last tracked, and whether they need respawning.
+    dirty: HashMap<i32, bool>,
+    pending_respawns: Vec<(Handle<LdtkAsset>, Level)>,
+    source_levels: HashMap<i32, Level>,
+    changes: HashMap<i32, Vec<LevelChange>>,
+}
+
+impl LdtkEditor {
+    /// The position of the mouse cursor, or [None] if it isn't over a window.
+    pub fn cursor(&self) -> Option<&EditorCursor> {
+        self.cursor.as_ref()
+    }
+
+    /// The entity being dragged, if any.
+    pub fn dragged_entity(&self) -> Option<Entity> {
+        self.drag.map(|drag| drag.entity)
+    }
+
+    /// The level with the given uid as it was before it was first edited, or [None] if it hasn't
+    /// been edited.
+    pub fn source_level(&self, level_uid: i32) -> Option<&Level> {
+        self.source_levels.get(&level_uid)
+    }
+
+    /// The changes made to the level with the given uid, compared to its
+    /// [source level](LdtkEditor::source_level).
+    pub fn changes(&self, level_uid: i32) -> &[LevelChange] {
+        self.changes
+            .get(&level_uid)
+            .map(|changes| changes.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The uids of the levels that have changes.
+    pub fn edited_levels(&self) -> impl Iterator<Item = i32> + '_ {
+        self.changes
+            .iter()
+            .filter(|(_, changes)| !changes.is_empty())
+            .map(|(level_uid, _)| *level_uid)
+    }
+
+    fn mark_dirty(&mut self, level_uid: i32, respawn: bool) {
+        *self.dirty.entry(level_uid).or_default() |= respawn;
+    }
+}
+
+/// Converts a cursor position in a window to world space, as seen by the given camera.
+pub fn cursor_to_world_position(
+    cursor_position: Vec2,
+    window_size: Vec2,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Vec2 {
+    let ndc = (cursor_position / window_size) * 2. - Vec2::ONE;
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix.inverse();
+
+    ndc_to_world.project_point3(ndc.extend(-1.)).truncate()
+}
+
+fn world_to_local(world_position: Vec2, transform: &GlobalTransform) -> Vec2 {
+    transform
+        .compute_matrix()
+        .inverse()
+        .transform_point3(world_position.extend(0.))
+        .truncate()
+}
+
+/// Tracks the position of the mouse cursor in the world, and the level and cell under it.
+///
+/// Uses the 2d camera of the window the cursor is in.
+pub fn update_editor_cursor(
+    mut editor: ResMut<LdtkEditor>,
+    settings: Res<LdtkEditorSettings>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    level_query: Query<(Entity, &LevelUid, &Handle<LdtkLevel>, &GlobalTransform)>,
+    layer_query: Query<(&LdtkLayer, &GlobalTransform, &Parent), With<IntGrid>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+) {
+    let world_position = camera_query
+        .iter()
+        .filter(|(camera, _)| camera.name.as_deref() == Some(CameraPlugin::CAMERA_2D))
+        .find_map(|(camera, camera_transform)| {
+            let window = windows.get(camera.window)?;
+            let cursor_position = window.cursor_position()?;
+
+            Some(cursor_to_world_position(
+                cursor_position,
+                Vec2::new(window.width(), window.height()),
+                camera,
+                camera_transform,
+            ))
+        });
+
+    editor.cursor = world_position.map(|world_position| {
+        let level = level_query
+            .iter()
+            .find(|(_, _, level_handle, level_transform)| {
+                level_assets
+                    .get(*level_handle)
+                    .map(|ldtk_level| {
+                        let level_size =
+                            IVec2::new(ldtk_level.level.px_wid, ldtk_level.level.px_hei);
+                        let local = world_to_local(world_position, level_transform);
+
+                        local.cmpge(Vec2::ZERO).all() && local.cmplt(level_size.as_vec2()).all()
+                    })
+                    .unwrap_or(false)
+            });
+
+        let grid_coords = level.and_then(|(level_entity, ..)| {
+            let paint_layer = settings.paint_layer.as_ref()?;
+
+            layer_query
+                .iter()
+                .find(|(layer, _, parent)| {
+                    parent.0 == level_entity && layer.identifier == *paint_layer
+                })
+                .map(|(layer, layer_transform, _)| {
+                    translation_to_grid_coords(
+                        world_to_local(world_position, layer_transform),
+                        IVec2::splat(layer.grid_size),
+                    )
+                })
+        });
+
+        EditorCursor {
+            world_position,
+            level_entity: level.map(|(level_entity, ..)| level_entity),
+            level_uid: level.map(|(_, level_uid, ..)| level_uid.0),
+            grid_coords,
+        }
+    });
+}
+
+/// Drags entities with an [EntityInstance] while the left mouse button is held on them.
+#[allow(clippy::too_many_arguments)]
+pub fn drag_ldtk_entities(
+    mut editor: ResMut<LdtkEditor>,
+    settings: Res<LdtkEditorSettings>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    entity_query: Query<(Entity, &EntityInstance, &GlobalTransform)>,
+    mut transform_query: Query<(&mut Transform, Option<&Parent>)>,
+    global_transform_query: Query<&GlobalTransform>,
+    parent_query: Query<&Parent>,
+    level_uid_query: Query<&LevelUid>,
+    worldly_query: Query<&Worldly>,
+) {
+    let cursor = match (settings.enabled, editor.cursor) {
+        (true, Some(cursor)) if mouse_buttons.pressed(MouseButton::Left) => cursor.world_position,
+        _ => {
+            if let Some(drag) = editor.drag.take() {
+                editor.mark_dirty(drag.level_uid, false);
+            }
+            return;
+        }
+    };
+
+    if editor.drag.is_none() && mouse_buttons.just_pressed(MouseButton::Left) {
+        let level_uid = |entity: Entity| match worldly_query.get(entity) {
+            Ok(worldly) => Some(worldly.spawn_level),
+            Err(_) => {
+                let layer = parent_query.get(entity).ok()?.0;
+                let level = parent_query.get(layer).ok()?.0;
+                level_uid_query.get(level).ok().map(|level_uid| level_uid.0)
+            }
+        };
+
+        editor.drag = entity_query
+            .iter()
+            .filter(|(_, entity_instance, transform)| {
+                let half_size =
+                    IVec2::new(entity_instance.width, entity_instance.height).as_vec2() / 2.;
+
+                (cursor - transform.translation.truncate())
+                    .abs()
+                    .cmple(half_size)
+                    .all()
+            })
+            .filter_map(|(entity, _, transform)| {
+                level_uid(entity).map(|level_uid| (entity, level_uid, transform))
+            })
+            .max_by(|(.., a), (.., b)| a.translation.z.total_cmp(&b.translation.z))
+            .map(|(entity, level_uid, transform)| Drag {
+                entity,
+                level_uid,
+                grab_offset: transform.translation.truncate() - cursor,
+            });
+    }
+
+    if let Some(drag) = editor.drag {
+        let z = match global_transform_query.get(drag.entity) {
+            Ok(global_transform) => global_transform.translation.z,
+            Err(_) => {
+                editor.drag = None;
+                return;
+            }
+        };
+
+        if let Ok((mut transform, parent)) = transform_query.get_mut(drag.entity) {
+            let target = (cursor + drag.grab_offset).extend(z);
+
+            let local = match parent.and_then(|p| global_transform_query.get(p.0).ok()) {
+                Some(parent_transform) => parent_transform
+                    .compute_matrix()
+                    .inverse()
+                    .transform_point3(target),
+                None => target,
+            };
+
+            if transform.translation.truncate() != local.truncate() {
+                transform.translation.x = local.x;
+                transform.translation.y = local.y;
+            }
+        }
+    }
+}
+
+/// Paints the [LdtkEditorSettings::paint_layer] while the left mouse button is held, and erases it
+/// while the right mouse button is held.
+pub fn paint_int_grid(
+    mut editor: ResMut<LdtkEditor>,
+    settings: Res<LdtkEditorSettings>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut layer_query: Query<(&LdtkLayer, &mut IntGrid, &Parent)>,
+) {
+    let value = if mouse_buttons.pressed(MouseButton::Left) {
+        Some(settings.paint_value)
+    } else if mouse_buttons.pressed(MouseButton::Right) {
+        Some(0)
+    } else {
+        None
+    };
+
+    let target = match (value, editor.cursor, &settings.paint_layer) {
+        (
+            Some(value),
+            Some(EditorCursor {
+                level_entity: Some(level_entity),
+                level_uid: Some(level_uid),
+                grid_coords: Some(grid_coords),
+                ..
+            }),
+            Some(paint_layer),
+        ) if settings.enabled && editor.drag.is_none() => {
+            Some((value, level_entity, level_uid, grid_coords, paint_layer))
+        }
+        _ => None,
+    };
+
+    match target {
+        Some((value, level_entity, level_uid, grid_coords, paint_layer)) => {
+            for (layer, mut int_grid, parent) in layer_query.iter_mut() {
+                if parent.0 == level_entity
+                    && layer.identifier == *paint_layer
+                    && int_grid
+                        .get(grid_coords)
+                        .map(|v| v != value)
+                        .unwrap_or(false)
+                {
+                    int_grid.set(grid_coords, value);
+                    editor.stroke.insert(level_uid);
+                }
+            }
+        }
+        None if value.is_none() || !settings.enabled => {
+            for level_uid in std::mem::take(&mut editor.stroke) {
+                editor.mark_dirty(level_uid, settings.respawn_painted_levels);
+            }
+        }
+        None => (),
+    }
+}
+
+/// Updates the [LevelChange]s of edited levels, and exports painted levels for respawning.
+pub fn track_level_changes(
+    mut editor: ResMut<LdtkEditor>,
+    exporter: LdtkLevelExporter,
+    level_query: Query<(Entity, &LevelUid, &Handle<LdtkLevel>, &Parent)>,
+    world_query: Query<&Handle<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+) {
+    let editor = &mut *editor;
+
+    for (level_uid, respawn) in std::mem::take(&mut editor.dirty) {
+        let (level_entity, level_handle, world_entity) = match level_query
+            .iter()
+            .find(|(_, uid, ..)| uid.0 == level_uid)
+        {
+            Some((level_entity, _, level_handle, parent)) => (level_entity, level_handle, parent.0),
+            None => continue,
+        };
+
+        let source = match editor.source_levels.entry(level_uid) {
+            Entry::Occupied(source) => source.into_mut(),
+            Entry::Vacant(entry) => match level_assets.get(level_handle) {
+                Some(ldtk_level) => entry.insert(ldtk_level.level.clone()),
+                None => continue,
+            },
+        };
+
+        if let Some(level) = exporter.export_level(level_entity) {
+            let changes = diff_levels(source, &level);
+            editor.changes.insert(level_uid, changes);
+        }
+
+        if respawn {
+            if let (Ok(ldtk_handle), Some(level)) = (
+                world_query.get(world_entity),
+                exporter.export_level_for_respawn(level_entity),
+            ) {
+                editor.pending_respawns.push((ldtk_handle.clone(), level));
+            }
+        }
+    }
+}
+
+/// Replaces painted levels in their [LdtkAsset], which respawns them.
+///
+/// Only the IntGrid values of the painted levels change, not their auto-layer tiles.
+pub fn respawn_painted_levels(
+    mut editor: ResMut<LdtkEditor>,
+    mut ldtk_assets: ResMut<Assets<LdtkAsset>>,
+    mut level_assets: ResMut<Assets<LdtkLevel>>,
+) {
+    for (ldtk_handle, level) in editor.pending_respawns.drain(..) {
+        if let Some(ldtk_asset) = ldtk_assets.get_mut(ldtk_handle) {
+            ldtk_asset.insert_level(level, &mut level_assets);
+        }
+    }
+}
+
+/// Lists the differences between the IntGrid values and entities of two versions of a level.
+///
+/// Entities of the same definition are matched by position first, and then in order.
+pub fn diff_levels(source: &Level, level: &Level) -> Vec<LevelChange> {
+    let mut changes = Vec::new();
+
+    for layer_instance in level.layer_instances.iter().flatten() {
+        let source_layer = match source
+            .layer_instances
+            .iter()
+            .flatten()
+            .find(|l| l.layer_def_uid == layer_instance.layer_def_uid)
+        {
+            Some(source_layer) => source_layer,
+            None => continue,
+        };
+
+        match layer_instance.layer_instance_type {
+            Type::IntGrid => {
+                for (i, (from, to)) in source_layer
+                    .int_grid_csv
+                    .iter()
+                    .zip(&layer_instance.int_grid_csv)
+                    .enumerate()
+                    .filter(|(_, (from, to))| from != to)
+                {
+                    let i = i as i32;
+                    let ldtk_grid_coords =
+                        IVec2::new(i % layer_instance.c_wid, i / layer_instance.c_wid);
+
+                    changes.push(LevelChange::IntGridValue {
+                        layer: layer_instance.identifier.clone(),
+                        grid_coords: ldtk_grid_coords_to_grid_coords(
+                            ldtk_grid_coords,
+                            layer_instance.c_hei,
+                        ),
+                        from: *from,
+                        to: *to,
+                    });
+                }
+            }
+            Type::Entities => diff_entities(source_layer, layer_instance, &mut changes),
+            _ => (),
+        }
+    }
+
+    changes
+}
+
+fn diff_entities(source: &LayerInstance, layer: &LayerInstance, changes: &mut Vec<LevelChange>) {
+    let mut def_uids: Vec<i32> = Vec::new();
+    for entity_instance in source
+        .entity_instances
+        .iter()
+        .chain(&layer.entity_instances)
+    {
+        if !def_uids.contains(&entity_instance.def_uid) {
+            def_uids.push(entity_instance.def_uid);
+        }
+    }
+
+    for def_uid in def_uids {
+        let mut sources: Vec<_> = source
+            .entity_instances
+            .iter()
+            .filter(|e| e.def_uid == def_uid)
+            .map(Some)
+            .collect();
+        let mut currents: Vec<_> = layer
+            .entity_instances
+            .iter()
+            .filter(|e| e.def_uid == def_uid)
+            .map(Some)
+            .collect();
+
+        // Entities that haven't moved are paired first, so that moving one entity doesn't shift
+        // the pairing of the rest.
+        let mut pairs = Vec::new();
+        for current in currents.iter_mut() {
+            let px = current.map(|c| c.px);
+            if let Some(source) = sources
+                .iter_mut()
+                .find(|s| s.map(|s| Some(s.px) == px).unwrap_or(false))
+            {
+                pairs.push((source.take().unwrap(), current.take().unwrap()));
+            }
+        }
+
+        let mut sources = sources.into_iter().flatten();
+        let mut currents = currents.into_iter().flatten();
+        loop {
+            match (sources.next(), currents.next()) {
+                (Some(from), Some(to)) => pairs.push((from, to)),
+                (Some(from), None) => changes.push(LevelChange::EntityRemoved {
+                    layer: layer.identifier.clone(),
+                    identifier: from.identifier.clone(),
+                    px: from.px,
+                }),
+                (None, Some(to)) => changes.push(LevelChange::EntityAdded {
+                    layer: layer.identifier.clone(),
+                    identifier: to.identifier.clone(),
+                    px: to.px,
+                }),
+                (None, None) => break,
+            }
+        }
+
+        for (from, to) in pairs {
+            if from.px != to.px {
+                changes.push(LevelChange::EntityMoved {
+                    layer: layer.identifier.clone(),
+                    identifier: to.identifier.clone(),
+                    from: from.px,
+                    to: to.px,
+                });
+            }
+
+            for field_instance in &to.field_instances {
+                if let Some(source_field) = from
+                    .field_instances
+                    .iter()
+                    .find(|f| f.identifier == field_instance.identifier)
+                {
+                    if source_field.value != field_instance.value {
+                        changes.push(LevelChange::EntityFieldChanged {
+                            layer: layer.identifier.clone(),
+                            identifier: to.identifier.clone(),
+                            px: to.px,
+                            field: field_instance.identifier.clone(),
+                            from: source_field.value.clone(),
+                            to: field_instance.value.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assets::parse_ldtk_json, ldtk::LdtkJson};
+    use bevy::{asset::AssetPlugin, render::camera::CameraProjection};
+
+    fn platformer() -> LdtkJson {
+        parse_ldtk_json(
+            include_bytes!("../assets/Typical_2D_platformer_example.ldtk"),
+            false,
+        )
+        .unwrap()
+    }
+
+    fn layer_mut<'a>(level: &'a mut Level, identifier: &str) -> &'a mut LayerInstance {
+        level
+            .layer_instances
+            .iter_mut()
+            .flatten()
+            .find(|l| l.identifier == identifier)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cursor_to_world_position() {
+        let mut camera_bundle = OrthographicCameraBundle::new_2d();
+        camera_bundle.orthographic_projection.update(800., 600.);
+        camera_bundle.camera.projection_matrix = camera_bundle
+            .orthographic_projection
+            .get_projection_matrix();
+
+        let camera_transform = GlobalTransform::from_xyz(100., 50., 999.9);
+        let window_size = Vec2::new(800., 600.);
+
+        let world_position = |cursor_position: Vec2| {
+            cursor_to_world_position(
+                cursor_position,
+                window_size,
+                &camera_bundle.camera,
+                &camera_transform,
+            )
+        };
+
+        assert!(world_position(Vec2::new(400., 300.)).abs_diff_eq(Vec2::new(100., 50.), 0.01));
+        assert!(world_position(Vec2::ZERO).abs_diff_eq(Vec2::new(-300., -250.), 0.01));
+        assert!(world_position(Vec2::new(800., 0.)).abs_diff_eq(Vec2::new(500., -250.), 0.01));
+    }
+
+    #[test]
+    fn test_diff_levels() {
+        let source = platformer().levels[0].clone();
+        let mut level = source.clone();
+
+        layer_mut(&mut level, "Collisions").int_grid_csv[1] = 2;
+
+        let entities = &mut layer_mut(&mut level, "Entities").entity_instances;
+        // Removes the chest, and swaps the mobs before moving the second one
+        entities.remove(1);
+        entities.swap(1, 2);
+        entities[1].px = IVec2::new(280, 288);
+        entities[3].field_instances[0].value = FieldValue::Bool(true);
+
+        assert_eq!(
+            diff_levels(&source, &level),
+            vec![
+                LevelChange::EntityRemoved {
+                    layer: "Entities".to_string(),
+                    identifier: "Chest".to_string(),
+                    px: IVec2::new(504, 288),
+                },
+                LevelChange::EntityMoved {
+                    layer: "Entities".to_string(),
+                    identifier: "Mob".to_string(),
+                    from: IVec2::new(264, 288),
+                    to: IVec2::new(280, 288),
+                },
+                LevelChange::EntityFieldChanged {
+                    layer: "Entities".to_string(),
+                    identifier: "Door".to_string(),
+                    px: IVec2::new(440, 288),
+                    field: "locked".to_string(),
+                    from: FieldValue::Bool(false),
+                    to: FieldValue::Bool(true),
+                },
+                LevelChange::IntGridValue {
+                    layer: "Collisions".to_string(),
+                    grid_coords: GridCoords { x: 1, y: 20 },
+                    from: 0,
+                    to: 2,
+                },
+            ]
+        );
+
+        assert_eq!(diff_levels(&source, &source), vec![]);
+    }
+
+    #[test]
+    fn test_drag_ldtk_entities() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<LdtkEditorSettings>()
+            .init_resource::<LdtkEditor>()
+            .add_system(drag_ldtk_entities);
+
+        // Transforms aren't propagated, so global transforms are given explicitly
+        let transforms = |translation: Vec3, parent_translation: Vec3| {
+            (
+                Transform::from_translation(translation - parent_translation),
+                GlobalTransform::from_translation(translation),
+            )
+        };
+        let entity_instance = EntityInstance {
+            width: 16,
+            height: 16,
+            ..Default::default()
+        };
+
+        let level_translation = Vec3::new(100., 0., 0.);
+        let top = Vec3::new(108., 8., 1.);
+        let (mut top_entity, mut bottom_entity) = (None, None);
+        app.world
+            .spawn()
+            .insert(LevelUid(10))
+            .insert_bundle(transforms(level_translation, Vec3::ZERO))
+            .with_children(|level| {
+                level
+                    .spawn()
+                    .insert_bundle(transforms(level_translation, level_translation))
+                    .with_children(|layer| {
+                        top_entity = Some(
+                            layer
+                                .spawn()
+                                .insert(entity_instance.clone())
+                                .insert_bundle(transforms(top, level_translation))
+                                .id(),
+                        );
+                        bottom_entity = Some(
+                            layer
+                                .spawn()
+                                .insert(entity_instance.clone())
+                                .insert_bundle(transforms(
+                                    Vec3::new(112., 8., 0.),
+                                    level_translation,
+                                ))
+                                .id(),
+                        );
+                    });
+            });
+
+        // Adopted worldly entities are children of the world, not of their level
+        let worldly_translation = Vec3::new(300., 300., 2.);
+        let mut worldly_entity = None;
+        app.world
+            .spawn()
+            .insert_bundle(transforms(Vec3::ZERO, Vec3::ZERO))
+            .with_children(|world| {
+                worldly_entity = Some(
+                    world
+                        .spawn()
+                        .insert_bundle((
+                            entity_instance.clone(),
+                            Worldly {
+                                spawn_level: 20,
+                                ..Default::default()
+                            },
+                        ))
+                        .insert_bundle(transforms(worldly_translation, Vec3::ZERO))
+                        .id(),
+                );
+            });
+
+        let drag = |app: &mut App, world_position: Vec2, pressed: bool| {
+            app.world.get_resource_mut::<LdtkEditor>().unwrap().cursor = Some(EditorCursor {
+                world_position,
+                level_entity: None,
+                level_uid: None,
+                grid_coords: None,
+            });
+
+            // Only the first update of a press has the button just pressed
+            let mut mouse_buttons = app.world.get_resource_mut::<Input<MouseButton>>().unwrap();
+            mouse_buttons.clear();
+            if pressed {
+                mouse_buttons.press(MouseButton::Left);
+            } else {
+                mouse_buttons.release(MouseButton::Left);
+            }
+
+            app.update();
+            app.world.get_resource::<LdtkEditor>().unwrap().drag
+        };
+
+        // Nothing is grabbed away from entities
+        assert_eq!(drag(&mut app, Vec2::new(50., 50.), true), None);
+        drag(&mut app, Vec2::new(50., 50.), false);
+
+        // The topmost entity under the cursor is grabbed
+        let grabbed = drag(&mut app, Vec2::new(110., 10.), true).unwrap();
+        assert_eq!(grabbed.entity, top_entity.unwrap());
+        assert_eq!(grabbed.level_uid, 10);
+
+        // It follows the cursor, keeping the offset at which it was grabbed
+        drag(&mut app, Vec2::new(130., 20.), true);
+        assert_eq!(
+            app.world
+                .get::<Transform>(top_entity.unwrap())
+                .unwrap()
+                .translation,
+            Vec3::new(28., 18., 1.)
+        );
+        assert_eq!(
+            app.world
+                .get::<Transform>(bottom_entity.unwrap())
+                .unwrap()
+                .translation,
+            Vec3::new(12., 8., 0.)
+        );
+
+        // Releasing the mouse button drops it, marking its level as edited
+        assert_eq!(drag(&mut app, Vec2::new(130., 20.), false), None);
+        assert_eq!(
+            app.world.get_resource::<LdtkEditor>().unwrap().dirty,
+            HashMap::from([(10, false)])
+        );
+
+        // Worldly entities are attributed to the level they spawned in
+        let grabbed = drag(&mut app, Vec2::new(300., 300.), true).unwrap();
+        assert_eq!(grabbed.entity, worldly_entity.unwrap());
+        assert_eq!(grabbed.level_uid, 20);
+    }
+
+    #[test]
+    fn test_painting_tracks_changes_and_respawns_level() {
+        let project = platformer();
+        let original = project.levels[0].clone();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<LdtkAsset>()
+            .add_asset::<LdtkLevel>()
+            .init_resource::<Input<MouseButton>>()
+            .insert_resource(LdtkEditorSettings {
+                paint_layer: Some("Collisions".to_string()),
+                paint_value: 2,
+                ..Default::default()
+            })
+            .init_resource::<LdtkEditor>()
+            .add_system(paint_int_grid.label(LdtkEditorSystemLabel::Painting))
+            .add_system(
+                track_level_changes
+                    .label(LdtkEditorSystemLabel::Tracking)
+                    .after(LdtkEditorSystemLabel::Painting),
+            )
+            .add_system(respawn_painted_levels.after(LdtkEditorSystemLabel::Tracking));
+
+        let level_handle = app
+            .world
+            .get_resource_mut::<Assets<LdtkLevel>>()
+            .unwrap()
+            .add(LdtkLevel {
+                level: original.clone(),
+            });
+
+        let ldtk_handle = app
+            .world
+            .get_resource_mut::<Assets<LdtkAsset>>()
+            .unwrap()
+            .add(LdtkAsset {
+                project,
+                tileset_map: HashMap::new(),
+                level_map: HashMap::from([(original.uid, level_handle.clone())]),
+                level_paths: HashMap::new(),
+            });
+
+        let mut level_entity = None;
+        app.world
+            .spawn()
+            .insert(ldtk_handle.clone())
+            .with_children(|world| {
+                level_entity = Some(
+                    world
+                        .spawn()
+                        .insert_bundle((
+                            LevelUid(original.uid),
+                            level_handle.clone(),
+                            Transform::default(),
+                        ))
+                        .with_children(|level| {
+                            let layer_instance = original
+                                .layer_instances
+                                .iter()
+                                .flatten()
+                                .find(|l| l.identifier == "Collisions")
+                                .unwrap();
+
+                            level.spawn().insert_bundle((
+                                LdtkLayer::from_layer_instance(layer_instance, 0.),
+                                IntGrid::from_layer_instance(layer_instance),
+                            ));
+                        })
+                        .id(),
+                );
+            });
+
+        let grid_coords = GridCoords { x: 1, y: 20 };
+        app.world.get_resource_mut::<LdtkEditor>().unwrap().cursor = Some(EditorCursor {
+            world_position: Vec2::new(24., 328.),
+            level_entity,
+            level_uid: Some(original.uid),
+            grid_coords: Some(grid_coords),
+        });
+
+        app.world
+            .get_resource_mut::<Input<MouseButton>>()
+            .unwrap()
+            .press(MouseButton::Left);
+        app.update();
+
+        // Changes are tracked once the stroke ends
+        let editor = app.world.get_resource::<LdtkEditor>().unwrap();
+        assert!(editor.changes(original.uid).is_empty());
+        assert!(editor.source_level(original.uid).is_none());
+
+        app.world
+            .get_resource_mut::<Input<MouseButton>>()
+            .unwrap()
+            .release(MouseButton::Left);
+        app.update();
+
+        let editor = app.world.get_resource::<LdtkEditor>().unwrap();
+        assert_eq!(
+            editor.changes(original.uid),
+            [LevelChange::IntGridValue {
+                layer: "Collisions".to_string(),
+                grid_coords,
+                from: 0,
+                to: 2,
+            }]
+        );
+        assert_eq!(editor.source_level(original.uid), Some(&original));
+        assert_eq!(
+            editor.edited_levels().collect::<Vec<_>>(),
+            vec![original.uid]
+        );
+
+        let ldtk_asset = app
+            .world
+            .get_resource::<Assets<LdtkAsset>>()
+            .unwrap()
+            .get(ldtk_handle)
+            .unwrap();
+        assert_ne!(ldtk_asset.level_map[&original.uid], level_handle);
+        assert_eq!(
+            diff_levels(&original, &ldtk_asset.project.levels[0]),
+            editor.changes(original.uid)
+        );
+    }
+}
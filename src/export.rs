@@ -0,0 +1,539 @@
+//! Exporting the runtime state of spawned levels back to LDtk files.
+//!
+//! The [LdtkLevelExporter] system parameter reads a spawned level's IntGrid values, entity
+//! positions, and entity field values back into a [Level].
+//! That level can then be written to disk with [save_level], or saved into its project with
+//! [save_level_into_project], so LDtk can open it.
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{export::LdtkLevelExporter, prelude::*};
+//!
+//! fn save_on_f5(
+//!     input: Res<Input<KeyCode>>,
+//!     exporter: LdtkLevelExporter,
+//!     level_query: Query<Entity, With<LevelUid>>,
+//! ) {
+//!     if input.just_pressed(KeyCode::F5) {
+//!         for level_entity in level_query.iter() {
+//!             if let Some(level) = exporter.export_level(level_entity) {
+//!                 let path = format!("assets/exported/{}.ldtkl", level.identifier);
+//!                 bevy_ecs_ldtk::export::save_level(&level, path).unwrap();
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{
+    assets::LdtkLevel,
+    components::{EntityInstance, IntGrid, LdtkLayer, LevelUid, Worldly},
+    ldtk::{real_editor_values, FieldInstance, FieldValue, LayerInstance, LdtkJson, Level, Type},
+    utils::translation_to_ldtk_pixel_coords_pivoted,
+};
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::Serialize;
+use std::{fs, io, path::Path};
+
+/// [SystemParam] for exporting the current state of spawned levels as [Level]s.
+///
+/// The exported level starts as a copy of the level's [LdtkLevel] asset, and is updated with:
+/// - the values of each IntGrid layer's [IntGrid] component,
+/// - the positions and field values of the [EntityInstance] components of each Entity layer's
+///   children, including [Worldly] entities spawned in the level.
+///
+/// Entities are exported from their [EntityInstance] components, so entities spawned from
+/// [LdtkEntity](crate::app::LdtkEntity) bundles without one are left out of the exported level.
+/// Tiles, and layers that aren't spawned, are exported as they were loaded.
+/// So the auto-layer tiles of an exported IntGrid layer can be stale relative to its
+/// `int_grid_csv`, until the level is saved again in LDtk.
+#[derive(SystemParam)]
+pub struct LdtkLevelExporter<'w, 's> {
+    level_query: Query<
+        'w,
+        's,
+        (
+            &'static LevelUid,
+            &'static Handle<LdtkLevel>,
+            &'static Transform,
+            &'static Children,
+        ),
+    >,
+    layer_query: Query<
+        'w,
+        's,
+        (
+            &'static LdtkLayer,
+            Option<&'static IntGrid>,
+            Option<&'static Children>,
+        ),
+    >,
+    entity_query: Query<'w, 's, (&'static EntityInstance, &'static Transform)>,
+    worldly_query: Query<
+        'w,
+        's,
+        (
+            &'static EntityInstance,
+            &'static Transform,
+            &'static Worldly,
+            &'static Parent,
+        ),
+    >,
+    level_assets: Res<'w, Assets<LdtkLevel>>,
+}
+
+impl<'w, 's> LdtkLevelExporter<'w, 's> {
+    /// Exports the current state of the given level entity.
+    ///
+    /// Returns [None] if the entity is not a level, or its level asset isn't loaded.
+    pub fn export_level(&self, level_entity: Entity) -> Option<Level> {
+        self.export_level_with_worldly(level_entity, false)
+    }
+
+    /// Exports the current state of the given level entity for respawning it.
+    ///
+    /// Adopted [Worldly] entities are exported as they were spawned, since they are recognized by
+    /// their spawn position and would be spawned again if it changed.
+    pub(crate) fn export_level_for_respawn(&self, level_entity: Entity) -> Option<Level> {
+        self.export_level_with_worldly(level_entity, true)
+    }
+
+    fn export_level_with_worldly(
+        &self,
+        level_entity: Entity,
+        keep_spawned_worldly: bool,
+    ) -> Option<Level> {
+        let (level_uid, level_handle, level_transform, children) =
+            self.level_query.get(level_entity).ok()?;
+        let mut level = self.level_assets.get(level_handle)?.level.clone();
+
+        let layers: Vec<_> = children
+            .iter()
+            .filter_map(|child| self.layer_query.get(*child).ok().map(|l| (*child, l)))
+            .collect();
+
+        let px_hei = level.px_hei;
+        for layer_instance in level.layer_instances.iter_mut().flatten() {
+            let spawned_layers: Vec<_> = layers
+                .iter()
+                .filter(|(_, (layer, ..))| layer.layer_def_uid == layer_instance.layer_def_uid)
+                .collect();
+
+            if spawned_layers.is_empty() {
+                continue;
+            }
+
+            match layer_instance.layer_instance_type {
+                Type::IntGrid => {
+                    if let Some(int_grid) =
+                        spawned_layers.iter().filter_map(|(_, (_, i, _))| *i).next()
+                    {
+                        layer_instance.int_grid_csv = int_grid.int_grid_csv().to_vec();
+                    }
+                }
+                Type::Entities => {
+                    let mut entities = Vec::new();
+
+                    for (layer_entity, (_, _, layer_children)) in spawned_layers {
+                        for child in layer_children.iter().flat_map(|c| c.iter()) {
+                            if let Ok((entity_instance, transform)) = self.entity_query.get(*child)
+                            {
+                                entities.push(export_entity_instance(
+                                    entity_instance,
+                                    transform.translation.truncate(),
+                                    layer_instance,
+                                    px_hei,
+                                ));
+                            }
+                        }
+
+                        // Adopted worldly entities are children of the world instead.
+                        for (entity_instance, transform, worldly, parent) in
+                            self.worldly_query.iter()
+                        {
+                            if worldly.spawn_level == level_uid.0
+                                && worldly.spawn_layer == layer_instance.layer_def_uid
+                                && parent.0 != *layer_entity
+                            {
+                                entities.push(if keep_spawned_worldly {
+                                    entity_instance.clone()
+                                } else {
+                                    export_entity_instance(
+                                        entity_instance,
+                                        (transform.translation - level_transform.translation)
+                                            .truncate(),
+                                        layer_instance,
+                                        px_hei,
+                                    )
+                                });
+                            }
+                        }
+                    }
+
+                    layer_instance.entity_instances = entities;
+                }
+                _ => (),
+            }
+        }
+
+        Some(level)
+    }
+}
+
+/// Updates the position of an entity instance to match its translation, and the editor values of
+/// its fields to match their values.
+fn export_entity_instance(
+    entity_instance: &EntityInstance,
+    translation: Vec2,
+    layer_instance: &LayerInstance,
+    level_px_hei: i32,
+) -> EntityInstance {
+    let px = translation_to_ldtk_pixel_coords_pivoted(
+        translation,
+        level_px_hei,
+        IVec2::new(entity_instance.width, entity_instance.height),
+        entity_instance.pivot,
+    );
+
+    EntityInstance {
+        grid: entity_grid_coords(px, entity_instance.pivot, layer_instance.grid_size),
+        px,
+        field_instances: entity_instance
+            .field_instances
+            .iter()
+            .map(|f| match f.value {
+                FieldValue::Unknown(_) => f.clone(),
+                _ => FieldInstance {
+                    real_editor_values: real_editor_values(&f.value),
+                    ..f.clone()
+                },
+            })
+            .collect(),
+        ..entity_instance.clone()
+    }
+}
+
+/// The cell containing an entity's pixel coordinates, as LDtk computes it.
+///
+/// Entities pivoted on their right or bottom edge belong to the cell before that edge.
+fn entity_grid_coords(px: IVec2, pivot: Vec2, grid_size: i32) -> IVec2 {
+    let edge_adjustment = IVec2::new(
+        if pivot.x == 1. { -1 } else { 0 },
+        if pivot.y == 1. { -1 } else { 0 },
+    );
+
+    IVec2::new(
+        (px.x + edge_adjustment.x).div_euclid(grid_size),
+        (px.y + edge_adjustment.y).div_euclid(grid_size),
+    )
+}
+
+/// Writes a level to disk as an external level file (`.ldtkl`).
+pub fn save_level(level: &Level, path: impl AsRef<Path>) -> io::Result<()> {
+    write_json(level, path.as_ref())
+}
+
+/// Writes a project to disk as an LDtk project file (`.ldtk`).
+pub fn save_project(project: &LdtkJson, path: impl AsRef<Path>) -> io::Result<()> {
+    write_json(project, path.as_ref())
+}
+
+/// Replaces a level of the project and saves the project to the given path, the way LDtk would.
+///
+/// For projects with external levels, the level is written to its external level file, relative
+/// to the project, and the project only keeps the level's metadata.
+/// The level must already be in the project.
+pub fn save_level_into_project(
+    project: &mut LdtkJson,
+    project_path: impl AsRef<Path>,
+    level: Level,
+) -> io::Result<()> {
+    let project_path = project_path.as_ref();
+    let external_levels = project.external_levels;
+
+    let existing = project
+        .levels
+        .iter_mut()
+        .find(|l| l.uid == level.uid)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("project has no level with uid {}", level.uid),
+            )
+        })?;
+
+    match (external_levels, existing.external_rel_path.clone()) {
+        (true, Some(rel_path)) => {
+            let level_path = project_path
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(&rel_path);
+
+            save_level(
+                &Level {
+                    external_rel_path: None,
+                    ..level.clone()
+                },
+                level_path,
+            )?;
+
+            *existing = Level {
+                external_rel_path: Some(rel_path),
+                layer_instances: None,
+                ..level
+            };
+        }
+        _ => *existing = level,
+    }
+
+    save_project(project, project_path)
+}
+
+fn write_json<T: Serialize>(value: &T, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, serde_json::to_vec_pretty(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assets::parse_ldtk_json, utils::ldtk_pixel_coords_to_translation_pivoted};
+    use bevy::{asset::AssetPlugin, ecs::system::SystemState};
+
+    fn platformer() -> LdtkJson {
+        parse_ldtk_json(
+            include_bytes!("../assets/Typical_2D_platformer_example.ldtk"),
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_entity_grid_coords() {
+        // Player and Chest from the platformer example
+        assert_eq!(
+            entity_grid_coords(IVec2::new(184, 208), Vec2::new(0.5, 1.), 16),
+            IVec2::new(11, 12)
+        );
+        assert_eq!(
+            entity_grid_coords(IVec2::new(504, 288), Vec2::new(0.5, 1.), 16),
+            IVec2::new(31, 17)
+        );
+        assert_eq!(
+            entity_grid_coords(IVec2::new(-8, 0), Vec2::ZERO, 16),
+            IVec2::new(-1, 0)
+        );
+    }
+
+    #[test]
+    fn test_exported_level_has_runtime_state() {
+        let project = platformer();
+        let original = project.levels[0].clone();
+        let px_hei = original.px_hei;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<LdtkLevel>();
+
+        let level_handle = app
+            .world
+            .get_resource_mut::<Assets<LdtkLevel>>()
+            .unwrap()
+            .add(LdtkLevel {
+                level: original.clone(),
+            });
+
+        let mut int_grid = None;
+        let mut player = None;
+        let level_entity = app
+            .world
+            .spawn()
+            .insert_bundle((
+                LevelUid(original.uid),
+                level_handle,
+                Transform::from_xyz(100., 0., 0.),
+            ))
+            .with_children(|level| {
+                for layer_instance in original.layer_instances.iter().flatten() {
+                    let mut layer = level.spawn();
+                    layer.insert(LdtkLayer::from_layer_instance(layer_instance, 0.));
+
+                    match layer_instance.layer_instance_type {
+                        Type::IntGrid => {
+                            layer.insert(IntGrid::from_layer_instance(layer_instance));
+                            int_grid = Some(layer.id());
+                        }
+                        Type::Entities => {
+                            layer.with_children(|layer| {
+                                for entity_instance in &layer_instance.entity_instances {
+                                    let translation = ldtk_pixel_coords_to_translation_pivoted(
+                                        entity_instance.px,
+                                        px_hei,
+                                        IVec2::new(entity_instance.width, entity_instance.height),
+                                        entity_instance.pivot,
+                                    );
+
+                                    let id = layer
+                                        .spawn()
+                                        .insert_bundle((
+                                            entity_instance.clone(),
+                                            Transform::from_translation(translation.extend(0.)),
+                                        ))
+                                        .id();
+
+                                    if entity_instance.identifier == "Player" {
+                                        player = Some(id);
+                                    }
+                                }
+                            });
+                        }
+                        _ => (),
+                    }
+                }
+            })
+            .id();
+
+        // Unchanged levels are exported as they were loaded, besides editor values being written
+        // out for fields LDtk left at their defaults.
+        let mut exporter_state = SystemState::<LdtkLevelExporter>::new(&mut app.world);
+        let mut expected = original.clone();
+        for entity_instance in expected
+            .layer_instances
+            .iter_mut()
+            .flatten()
+            .flat_map(|l| l.entity_instances.iter_mut())
+        {
+            for field_instance in entity_instance.field_instances.iter_mut() {
+                field_instance.real_editor_values = real_editor_values(&field_instance.value);
+            }
+        }
+        assert_eq!(
+            exporter_state
+                .get_mut(&mut app.world)
+                .export_level(level_entity)
+                .unwrap(),
+            expected
+        );
+
+        app.world
+            .get_mut::<IntGrid>(int_grid.unwrap())
+            .unwrap()
+            .set(crate::components::GridCoords { x: 0, y: 0 }, 0);
+
+        let player = player.unwrap();
+        app.world.get_mut::<Transform>(player).unwrap().translation += Vec3::new(32., -16., 0.);
+
+        let exported = exporter_state
+            .get_mut(&mut app.world)
+            .export_level(level_entity)
+            .unwrap();
+
+        let layer = |level: &Level, identifier: &str| {
+            level
+                .layer_instances
+                .iter()
+                .flatten()
+                .find(|l| l.identifier == identifier)
+                .unwrap()
+                .clone()
+        };
+
+        let collisions = layer(&exported, "Collisions");
+        let last_row = (collisions.c_hei - 1) * collisions.c_wid;
+        assert_eq!(collisions.int_grid_csv[last_row as usize], 0);
+
+        let original_player = layer(&original, "Entities")
+            .entity_instances
+            .into_iter()
+            .find(|e| e.identifier == "Player")
+            .unwrap();
+        let exported_player = layer(&exported, "Entities")
+            .entity_instances
+            .into_iter()
+            .find(|e| e.identifier == "Player")
+            .unwrap();
+        assert_eq!(exported_player.px, original_player.px + IVec2::new(32, 16));
+        assert_eq!(
+            exported_player.grid,
+            original_player.grid + IVec2::new(2, 1)
+        );
+    }
+
+    #[test]
+    fn test_unspawned_entity_layer_is_exported_unchanged() {
+        let project = platformer();
+        let original = project.levels[0].clone();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<LdtkLevel>();
+
+        let level_handle = app
+            .world
+            .get_resource_mut::<Assets<LdtkLevel>>()
+            .unwrap()
+            .add(LdtkLevel {
+                level: original.clone(),
+            });
+
+        // Only the IntGrid layer is spawned, like when the Entities layer has no entities
+        let level_entity = app
+            .world
+            .spawn()
+            .insert_bundle((LevelUid(original.uid), level_handle, Transform::default()))
+            .with_children(|level| {
+                for layer_instance in original.layer_instances.iter().flatten() {
+                    if layer_instance.layer_instance_type == Type::IntGrid {
+                        level.spawn().insert_bundle((
+                            LdtkLayer::from_layer_instance(layer_instance, 0.),
+                            IntGrid::from_layer_instance(layer_instance),
+                        ));
+                    }
+                }
+            })
+            .id();
+
+        let mut exporter_state = SystemState::<LdtkLevelExporter>::new(&mut app.world);
+        let exported = exporter_state
+            .get_mut(&mut app.world)
+            .export_level(level_entity)
+            .unwrap();
+
+        assert_eq!(exported, original);
+    }
+
+    #[test]
+    fn test_levels_are_saved_into_external_projects() {
+        let mut project = platformer();
+        project.external_levels = true;
+        project.levels[0].external_rel_path = Some("platformer/level_0.ldtkl".to_string());
+
+        let mut level = project.levels[0].clone();
+        level.external_rel_path = None;
+        level.layer_instances = project.levels[0].layer_instances.clone();
+        level.identifier = "Renamed".to_string();
+
+        let dir = std::env::temp_dir().join(format!("bevy_ecs_ldtk_export_{}", std::process::id()));
+        let project_path = dir.join("platformer.ldtk");
+
+        save_level_into_project(&mut project, &project_path, level.clone()).unwrap();
+
+        let saved_project = parse_ldtk_json(&fs::read(&project_path).unwrap(), false).unwrap();
+        assert_eq!(saved_project.levels[0].identifier, "Renamed");
+        assert_eq!(saved_project.levels[0].layer_instances, None);
+
+        let saved_level = crate::assets::parse_level(
+            &fs::read(dir.join("platformer/level_0.ldtkl")).unwrap(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(saved_level, level);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
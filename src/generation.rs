@@ -0,0 +1,1042 @@
+//! Tools for creating levels at runtime that spawn just like levels made in LDtk.
+//!
+//! A [LevelBuilder] constructs a [Level] against the [Definitions] of an existing project, so the
+//! generated level can use the project's layers, tilesets, and entities, along with any
+//! [LdtkEntity] and [LdtkIntCell] registrations.
+//! The finished level can be added to a loaded project with [LdtkAsset::insert_level], and then
+//! spawned by adding its uid to a [LevelSet].
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{generation::LevelBuilder, prelude::*};
+//!
+//! fn generate_room(
+//!     mut ldtk_assets: ResMut<Assets<LdtkAsset>>,
+//!     mut level_assets: ResMut<Assets<LdtkLevel>>,
+//!     mut world_query: Query<(&Handle<LdtkAsset>, &mut LevelSet)>,
+//! ) {
+//!     let (ldtk_handle, mut level_set) = world_query.single_mut();
+//!     let ldtk_asset = ldtk_assets.get_mut(ldtk_handle).unwrap();
+//!
+//!     let mut builder = LevelBuilder::new(&ldtk_asset.project.defs, 1000, "Room", 160, 160);
+//!     for x in 0..10 {
+//!         builder.set_int_grid_value("Collisions", IVec2::new(x, 9), 1).unwrap();
+//!     }
+//!     builder
+//!         .add_entity("Entities", "Player", IVec2::new(4, 8), &[])
+//!         .unwrap();
+//!
+//!     let level = builder.build();
+//!     ldtk_asset.insert_level(level, &mut level_assets);
+//!     level_set.uids.insert(1000);
+//! }
+//! ```
+//!
+//! Levels made in LDtk can also be used as templates, like rooms of a dungeon.
+//! A [LevelComposer] places several template levels into one new level, which is spawned the same
+//! way.
+//!
+//! Coordinates given to the builder are LDtk grid coordinates, with the origin at the top-left
+//! cell of the level and y increasing downwards.
+
+use crate::ldtk::{
+    field_value_from_json, real_editor_values, Definitions, EntityInstance, EntityInstanceTile,
+    FieldDefinition, FieldInstance, FieldValue, LayerDefinition, LayerInstance, Level,
+    TileInstance, TilesetDefinition, Type,
+};
+use bevy::prelude::*;
+use serde_json::{json, Value};
+use std::{error::Error, fmt};
+
+#[allow(unused_imports)]
+use crate::{
+    app::{LdtkEntity, LdtkIntCell},
+    assets::{LdtkAsset, LdtkLevel},
+    components::LevelSet,
+    ldtk::LdtkJson,
+};
+
+/// Errors that can occur while building a level with a [LevelBuilder].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum LevelBuilderError {
+    /// The level has no layer with the given identifier.
+    UnknownLayer(String),
+    /// The layer with the given identifier is not of the type required by the operation.
+    WrongLayerType {
+        layer: String,
+        expected: Type,
+        actual: Type,
+    },
+    /// The project has no entity definition with the given identifier.
+    UnknownEntity(String),
+    /// The entity or level has no field definition with the given identifier.
+    UnknownField(String),
+    /// The value given for a field doesn't match the type of its definition.
+    InvalidFieldValue { field: String, field_type: String },
+    /// The IntGrid layer has no value definition for the given value.
+    UnknownIntGridValue { layer: String, value: i32 },
+    /// The grid coordinates are outside of the layer.
+    OutOfBounds { layer: String, grid_coords: IVec2 },
+    /// The layer doesn't have a tileset, or its tileset definition doesn't exist.
+    MissingTileset { layer: String },
+    /// The tile id is outside of the layer's tileset.
+    UnknownTile { layer: String, tile_id: i32 },
+    /// A template was placed at a negative offset.
+    NegativeOffset(IVec2),
+    /// A template's pixel offset doesn't line up with the grid of one of its layers.
+    MisalignedOffset { layer: String, px_offset: IVec2 },
+    /// The template level with the given uid has no layer instances, like the levels of a project
+    /// with external levels.
+    MissingLayerInstances(i32),
+}
+
+impl fmt::Display for LevelBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelBuilderError::UnknownLayer(layer) => write!(f, "no layer named {:?}", layer),
+            LevelBuilderError::WrongLayerType {
+                layer,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "layer {:?} has type {:?}, expected {:?}",
+                layer, actual, expected
+            ),
+            LevelBuilderError::UnknownEntity(entity) => {
+                write!(f, "no entity definition named {:?}", entity)
+            }
+            LevelBuilderError::UnknownField(field) => {
+                write!(f, "no field definition named {:?}", field)
+            }
+            LevelBuilderError::InvalidFieldValue { field, field_type } => write!(
+                f,
+                "value of field {:?} does not match its type {:?}",
+                field, field_type
+            ),
+            LevelBuilderError::UnknownIntGridValue { layer, value } => {
+                write!(f, "layer {:?} has no IntGrid value {}", layer, value)
+            }
+            LevelBuilderError::OutOfBounds { layer, grid_coords } => write!(
+                f,
+                "grid coordinates {} are outside of layer {:?}",
+                grid_coords, layer
+            ),
+            LevelBuilderError::MissingTileset { layer } => {
+                write!(f, "layer {:?} has no tileset", layer)
+            }
+            LevelBuilderError::UnknownTile { layer, tile_id } => write!(
+                f,
+                "tile id {} is outside of the tileset of layer {:?}",
+                tile_id, layer
+            ),
+            LevelBuilderError::NegativeOffset(offset) => {
+                write!(f, "template offset {} is negative", offset)
+            }
+            LevelBuilderError::MisalignedOffset { layer, px_offset } => write!(
+                f,
+                "template pixel offset {} is not aligned with the grid of layer {:?}",
+                px_offset, layer
+            ),
+            LevelBuilderError::MissingLayerInstances(uid) => {
+                write!(f, "template level {} has no layer instances", uid)
+            }
+        }
+    }
+}
+
+impl Error for LevelBuilderError {}
+
+/// Builds a [Level] that uses the layers, tilesets, and entities of an existing project.
+///
+/// The level starts with an empty instance of every layer in the project, and every level field
+/// set to its default value.
+/// Its background color is black unless set with [LevelBuilder::set_bg_color].
+///
+/// Auto-layer tiles are not generated from IntGrid values, since that requires running LDtk's
+/// auto-layer rules.
+/// Tiles can be added to Tile layers with [LevelBuilder::add_tile] instead.
+#[derive(Clone, Debug)]
+pub struct LevelBuilder<'a> {
+    defs: &'a Definitions,
+    level: Level,
+}
+
+impl<'a> LevelBuilder<'a> {
+    /// Starts building a level with the given uid, identifier, and size in pixels.
+    ///
+    /// The uid should be unique within the project the level will be inserted into.
+    pub fn new(
+        defs: &'a Definitions,
+        uid: i32,
+        identifier: impl Into<String>,
+        px_wid: i32,
+        px_hei: i32,
+    ) -> Self {
+        let layer_instances = defs
+            .layers
+            .iter()
+            .map(|layer_def| empty_layer_instance(defs, layer_def, uid, px_wid, px_hei))
+            .collect();
+
+        let field_instances = defs
+            .level_fields
+            .iter()
+            .map(|field_def| field_instance(field_def, default_field_value(field_def)))
+            .collect();
+
+        LevelBuilder {
+            defs,
+            level: Level {
+                bg_color: Color::BLACK,
+                bg_pivot_x: 0.5,
+                bg_pivot_y: 0.5,
+                field_instances,
+                identifier: identifier.into(),
+                layer_instances: Some(layer_instances),
+                px_hei,
+                px_wid,
+                uid,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Sets the pixel coordinates of the level in the world, used with
+    /// [LdtkSettings::use_level_world_translations](crate::LdtkSettings::use_level_world_translations).
+    pub fn set_world_coords(&mut self, world_x: i32, world_y: i32) -> &mut Self {
+        self.level.world_x = world_x;
+        self.level.world_y = world_y;
+        self
+    }
+
+    /// Sets the background color of the level.
+    pub fn set_bg_color(&mut self, color: Color) -> &mut Self {
+        self.level.bg_color = color;
+        self.level.level_bg_color = Some(color);
+        self
+    }
+
+    /// Sets the value of one of the level's fields.
+    pub fn set_level_field(
+        &mut self,
+        identifier: &str,
+        value: FieldValue,
+    ) -> Result<&mut Self, LevelBuilderError> {
+        let field_def = find_field_definition(&self.defs.level_fields, identifier)?;
+        let field_instance = field_instance(field_def, checked_field_value(field_def, value)?);
+
+        match self
+            .level
+            .field_instances
+            .iter_mut()
+            .find(|f| f.def_uid == field_def.uid)
+        {
+            Some(existing) => *existing = field_instance,
+            None => self.level.field_instances.push(field_instance),
+        }
+
+        Ok(self)
+    }
+
+    /// Sets the value of a cell of an IntGrid layer.
+    ///
+    /// A value of `0` empties the cell.
+    pub fn set_int_grid_value(
+        &mut self,
+        layer_identifier: &str,
+        grid_coords: IVec2,
+        value: i32,
+    ) -> Result<&mut Self, LevelBuilderError> {
+        let layer_def = find_layer_definition(self.defs, layer_identifier)?;
+        if value != 0 && !layer_def.int_grid_values.iter().any(|v| v.value == value) {
+            return Err(LevelBuilderError::UnknownIntGridValue {
+                layer: layer_identifier.to_string(),
+                value,
+            });
+        }
+
+        let layer_instance = self.layer_instance_mut(layer_identifier, Type::IntGrid)?;
+        let index = grid_index(layer_instance, grid_coords)?;
+        layer_instance.int_grid_csv[index] = value;
+
+        Ok(self)
+    }
+
+    /// Adds a tile from the layer's tileset to a Tile layer.
+    pub fn add_tile(
+        &mut self,
+        layer_identifier: &str,
+        grid_coords: IVec2,
+        tile_id: i32,
+    ) -> Result<&mut Self, LevelBuilderError> {
+        self.add_flipped_tile(layer_identifier, grid_coords, tile_id, false, false)
+    }
+
+    /// Adds a tile from the layer's tileset to a Tile layer, flipped along the given axes.
+    pub fn add_flipped_tile(
+        &mut self,
+        layer_identifier: &str,
+        grid_coords: IVec2,
+        tile_id: i32,
+        flip_x: bool,
+        flip_y: bool,
+    ) -> Result<&mut Self, LevelBuilderError> {
+        let defs = self.defs;
+        let layer_instance = self.layer_instance_mut(layer_identifier, Type::Tiles)?;
+        let coord_id = grid_index(layer_instance, grid_coords)? as i32;
+
+        let tileset_def = layer_instance
+            .tileset_def_uid
+            .and_then(|uid| find_tileset_definition(defs, uid))
+            .ok_or_else(|| LevelBuilderError::MissingTileset {
+                layer: layer_identifier.to_string(),
+            })?;
+
+        if !(0..tileset_def.c_wid * tileset_def.c_hei).contains(&tile_id) {
+            return Err(LevelBuilderError::UnknownTile {
+                layer: layer_identifier.to_string(),
+                tile_id,
+            });
+        }
+
+        layer_instance.grid_tiles.push(TileInstance {
+            d: vec![coord_id],
+            f: flip_x as i32 | (flip_y as i32) << 1,
+            px: grid_coords * layer_instance.grid_size,
+            src: tile_src(tileset_def, tile_id),
+            t: tile_id,
+            ..Default::default()
+        });
+
+        Ok(self)
+    }
+
+    /// Adds an entity to an Entity layer, setting the given fields.
+    ///
+    /// The entity's pivot is placed in the given cell as LDtk would, and fields that aren't given
+    /// are set to their default values.
+    pub fn add_entity(
+        &mut self,
+        layer_identifier: &str,
+        entity_identifier: &str,
+        grid_coords: IVec2,
+        fields: &[(&str, FieldValue)],
+    ) -> Result<&mut Self, LevelBuilderError> {
+        let defs = self.defs;
+        let entity_def = defs
+            .entities
+            .iter()
+            .find(|e| e.identifier == entity_identifier)
+            .ok_or_else(|| LevelBuilderError::UnknownEntity(entity_identifier.to_string()))?;
+
+        let mut field_instances: Vec<FieldInstance> = entity_def
+            .field_defs
+            .iter()
+            .map(|field_def| field_instance(field_def, default_field_value(field_def)))
+            .collect();
+
+        for (identifier, value) in fields {
+            let field_def = find_field_definition(&entity_def.field_defs, identifier)?;
+            let field_value = checked_field_value(field_def, value.clone())?;
+
+            if let Some(existing) = field_instances
+                .iter_mut()
+                .find(|f| f.def_uid == field_def.uid)
+            {
+                *existing = field_instance(field_def, field_value);
+            }
+        }
+
+        let tile =
+            entity_def
+                .tileset_id
+                .zip(entity_def.tile_id)
+                .and_then(|(tileset_uid, tile_id)| {
+                    find_tileset_definition(defs, tileset_uid).map(|tileset_def| {
+                        let src = tile_src(tileset_def, tile_id);
+                        EntityInstanceTile {
+                            src_rect: vec![
+                                src.x,
+                                src.y,
+                                tileset_def.tile_grid_size,
+                                tileset_def.tile_grid_size,
+                            ],
+                            tileset_uid,
+                        }
+                    })
+                });
+
+        let layer_instance = self.layer_instance_mut(layer_identifier, Type::Entities)?;
+        grid_index(layer_instance, grid_coords)?;
+
+        let pivot = Vec2::new(entity_def.pivot_x, entity_def.pivot_y);
+        let grid_size = layer_instance.grid_size;
+
+        layer_instance.entity_instances.push(EntityInstance {
+            grid: grid_coords,
+            identifier: entity_def.identifier.clone(),
+            pivot,
+            tile,
+            def_uid: entity_def.uid,
+            field_instances,
+            height: entity_def.height,
+            px: grid_coords * grid_size + (pivot * grid_size as f32).as_ivec2(),
+            width: entity_def.width,
+        });
+
+        Ok(self)
+    }
+
+    /// Finishes building the level.
+    pub fn build(self) -> Level {
+        self.level
+    }
+
+    fn layer_instance_mut(
+        &mut self,
+        identifier: &str,
+        expected: Type,
+    ) -> Result<&mut LayerInstance, LevelBuilderError> {
+        let layer_instance = self
+            .level
+            .layer_instances
+            .iter_mut()
+            .flatten()
+            .find(|l| l.identifier == identifier)
+            .ok_or_else(|| LevelBuilderError::UnknownLayer(identifier.to_string()))?;
+
+        if layer_instance.layer_instance_type != expected {
+            return Err(LevelBuilderError::WrongLayerType {
+                layer: identifier.to_string(),
+                expected,
+                actual: layer_instance.layer_instance_type.clone(),
+            });
+        }
+
+        Ok(layer_instance)
+    }
+}
+
+/// Composes a new [Level] out of several template levels of the same project.
+///
+/// Templates are placed at offsets measured in cells of the `grid_size` given to
+/// [LevelComposer::new], which usually matches the project's default grid size.
+/// The resulting level is just big enough to fit every template.
+///
+/// The IntGrid values, tiles, and entities of each template are copied into the matching layers of
+/// the new level, with their coordinates moved by the template's offset.
+/// Templates added later are drawn over those added earlier, and their nonzero IntGrid values
+/// replace earlier ones.
+/// The new level gets the background color of the first template, and default level fields.
+///
+/// The levels of a project with external levels have no layer instances in its [LdtkJson], so
+/// templates from such projects must come from their [LdtkLevel] assets
+/// instead.
+#[derive(Clone, Debug)]
+pub struct LevelComposer<'a> {
+    defs: &'a Definitions,
+    grid_size: i32,
+    templates: Vec<(&'a Level, IVec2)>,
+}
+
+impl<'a> LevelComposer<'a> {
+    /// Starts composing a level, measuring template offsets in cells of the given size.
+    pub fn new(defs: &'a Definitions, grid_size: i32) -> Self {
+        LevelComposer {
+            defs,
+            grid_size,
+            templates: Vec::new(),
+        }
+    }
+
+    /// Places a template at the given offset from the top-left corner of the new level.
+    ///
+    /// Like other LDtk grid coordinates, y increases downwards.
+    pub fn add_template(&mut self, template: &'a Level, grid_offset: IVec2) -> &mut Self {
+        self.templates.push((template, grid_offset));
+        self
+    }
+
+    /// Builds the composite level with the given uid and identifier.
+    pub fn compose(
+        &self,
+        uid: i32,
+        identifier: impl Into<String>,
+    ) -> Result<Level, LevelBuilderError> {
+        let mut size = IVec2::ZERO;
+        for (template, grid_offset) in &self.templates {
+            if grid_offset.x < 0 || grid_offset.y < 0 {
+                return Err(LevelBuilderError::NegativeOffset(*grid_offset));
+            }
+
+            if template.layer_instances.is_none() {
+                return Err(LevelBuilderError::MissingLayerInstances(template.uid));
+            }
+
+            let template_size = IVec2::new(template.px_wid, template.px_hei);
+            size = size.max(*grid_offset * self.grid_size + template_size);
+        }
+
+        let mut builder = LevelBuilder::new(self.defs, uid, identifier, size.x, size.y);
+        if let Some((first, _)) = self.templates.first() {
+            builder.level.bg_color = first.bg_color;
+            builder.level.level_bg_color = first.level_bg_color;
+        }
+        let mut level = builder.build();
+
+        for (template, grid_offset) in &self.templates {
+            let px_offset = *grid_offset * self.grid_size;
+
+            for template_layer in template.layer_instances.iter().flatten() {
+                let layer = level
+                    .layer_instances
+                    .iter_mut()
+                    .flatten()
+                    .find(|l| l.layer_def_uid == template_layer.layer_def_uid)
+                    .ok_or_else(|| {
+                        LevelBuilderError::UnknownLayer(template_layer.identifier.clone())
+                    })?;
+
+                merge_layer_instance(layer, template_layer, px_offset)?;
+            }
+        }
+
+        Ok(level)
+    }
+}
+
+/// Returns the levels whose field with the given identifier has the given value, for choosing
+/// templates by their level fields.
+pub fn levels_with_field<'a>(
+    levels: &'a [Level],
+    field_identifier: &'a str,
+    value: &'a FieldValue,
+) -> impl Iterator<Item = &'a Level> {
+    levels.iter().filter(move |level| {
+        level
+            .field_instances
+            .iter()
+            .any(|f| f.identifier == field_identifier && f.value == *value)
+    })
+}
+
+/// Copies the contents of a template's layer into a layer of the composite level.
+fn merge_layer_instance(
+    layer: &mut LayerInstance,
+    template_layer: &LayerInstance,
+    px_offset: IVec2,
+) -> Result<(), LevelBuilderError> {
+    if px_offset % layer.grid_size != IVec2::ZERO {
+        return Err(LevelBuilderError::MisalignedOffset {
+            layer: layer.identifier.clone(),
+            px_offset,
+        });
+    }
+    let cell_offset = px_offset / layer.grid_size;
+
+    for (i, value) in template_layer.int_grid_csv.iter().enumerate() {
+        if *value != 0 {
+            let i = i as i32;
+            let grid_coords =
+                IVec2::new(i % template_layer.c_wid, i / template_layer.c_wid) + cell_offset;
+            let index = grid_index(layer, grid_coords)?;
+            layer.int_grid_csv[index] = *value;
+        }
+    }
+
+    // The last value of `d` is the id of the tile's cell, for both tile and auto-layer tiles.
+    let move_tile = |tile: &TileInstance| -> Result<TileInstance, LevelBuilderError> {
+        let px = tile.px + px_offset;
+        let coord_id = grid_index(layer, px / layer.grid_size)? as i32;
+
+        let mut d = tile.d.clone();
+        if let Some(last) = d.last_mut() {
+            *last = coord_id;
+        }
+
+        Ok(TileInstance {
+            d,
+            px,
+            ..tile.clone()
+        })
+    };
+
+    let grid_tiles = template_layer
+        .grid_tiles
+        .iter()
+        .map(&move_tile)
+        .collect::<Result<Vec<_>, _>>()?;
+    let auto_layer_tiles = template_layer
+        .auto_layer_tiles
+        .iter()
+        .map(&move_tile)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    layer.grid_tiles.extend(grid_tiles);
+    layer.auto_layer_tiles.extend(auto_layer_tiles);
+
+    for entity in &template_layer.entity_instances {
+        layer.entity_instances.push(EntityInstance {
+            grid: entity.grid + cell_offset,
+            px: entity.px + px_offset,
+            field_instances: entity
+                .field_instances
+                .iter()
+                .map(|f| move_points(f, cell_offset))
+                .collect(),
+            ..entity.clone()
+        });
+    }
+
+    Ok(())
+}
+
+/// Moves the grid-based points in a field by the given number of cells.
+fn move_points(field_instance: &FieldInstance, cell_offset: IVec2) -> FieldInstance {
+    let value = match &field_instance.value {
+        FieldValue::Point(point) => FieldValue::Point(point.map(|p| p + cell_offset)),
+        FieldValue::Points(points) => FieldValue::Points(
+            points
+                .iter()
+                .map(|point| point.map(|p| p + cell_offset))
+                .collect(),
+        ),
+        _ => return field_instance.clone(),
+    };
+
+    FieldInstance {
+        real_editor_values: real_editor_values(&value),
+        value,
+        ..field_instance.clone()
+    }
+}
+
+fn empty_layer_instance(
+    defs: &Definitions,
+    layer_def: &LayerDefinition,
+    level_uid: i32,
+    px_wid: i32,
+    px_hei: i32,
+) -> LayerInstance {
+    let c_wid = (px_wid + layer_def.grid_size - 1) / layer_def.grid_size;
+    let c_hei = (px_hei + layer_def.grid_size - 1) / layer_def.grid_size;
+
+    let tileset_def_uid = match layer_def.purple_type {
+        Type::Tiles => layer_def.tileset_def_uid,
+        _ => layer_def.auto_tileset_def_uid,
+    };
+
+    let int_grid_csv = match layer_def.purple_type {
+        Type::IntGrid => vec![0; (c_wid * c_hei) as usize],
+        _ => Vec::new(),
+    };
+
+    LayerInstance {
+        c_hei,
+        c_wid,
+        grid_size: layer_def.grid_size,
+        identifier: layer_def.identifier.clone(),
+        opacity: layer_def.display_opacity,
+        px_total_offset_x: layer_def.px_offset_x,
+        px_total_offset_y: layer_def.px_offset_y,
+        tileset_def_uid,
+        tileset_rel_path: tileset_def_uid
+            .and_then(|uid| find_tileset_definition(defs, uid))
+            .map(|t| t.rel_path.clone()),
+        layer_instance_type: layer_def.purple_type.clone(),
+        auto_layer_tiles: Vec::new(),
+        entity_instances: Vec::new(),
+        grid_tiles: Vec::new(),
+        int_grid: None,
+        int_grid_csv,
+        layer_def_uid: layer_def.uid,
+        level_id: level_uid,
+        optional_rules: Vec::new(),
+        override_tileset_uid: None,
+        px_offset_x: 0,
+        px_offset_y: 0,
+        seed: 0,
+        visible: true,
+    }
+}
+
+fn find_layer_definition<'a>(
+    defs: &'a Definitions,
+    identifier: &str,
+) -> Result<&'a LayerDefinition, LevelBuilderError> {
+    defs.layers
+        .iter()
+        .find(|l| l.identifier == identifier)
+        .ok_or_else(|| LevelBuilderError::UnknownLayer(identifier.to_string()))
+}
+
+fn find_tileset_definition(defs: &Definitions, uid: i32) -> Option<&TilesetDefinition> {
+    defs.tilesets.iter().find(|t| t.uid == uid)
+}
+
+fn find_field_definition<'a>(
+    field_defs: &'a [FieldDefinition],
+    identifier: &str,
+) -> Result<&'a FieldDefinition, LevelBuilderError> {
+    field_defs
+        .iter()
+        .find(|f| f.identifier == identifier)
+        .ok_or_else(|| LevelBuilderError::UnknownField(identifier.to_string()))
+}
+
+/// Index of the cell in LDtk's row-major order, if it's inside the layer.
+fn grid_index(
+    layer_instance: &LayerInstance,
+    grid_coords: IVec2,
+) -> Result<usize, LevelBuilderError> {
+    if (0..layer_instance.c_wid).contains(&grid_coords.x)
+        && (0..layer_instance.c_hei).contains(&grid_coords.y)
+    {
+        Ok((grid_coords.y * layer_instance.c_wid + grid_coords.x) as usize)
+    } else {
+        Err(LevelBuilderError::OutOfBounds {
+            layer: layer_instance.identifier.clone(),
+            grid_coords,
+        })
+    }
+}
+
+/// Pixel coordinates of the top-left corner of a tile in its tileset.
+fn tile_src(tileset_def: &TilesetDefinition, tile_id: i32) -> IVec2 {
+    let step = tileset_def.tile_grid_size + tileset_def.spacing;
+    let c_wid = tileset_def.c_wid.max(1);
+
+    IVec2::new(
+        tileset_def.padding + (tile_id % c_wid) * step,
+        tileset_def.padding + (tile_id / c_wid) * step,
+    )
+}
+
+fn field_instance(field_def: &FieldDefinition, value: FieldValue) -> FieldInstance {
+    FieldInstance {
+        identifier: field_def.identifier.clone(),
+        field_instance_type: field_def.field_definition_type.clone(),
+        real_editor_values: real_editor_values(&value),
+        value,
+        def_uid: field_def.uid,
+    }
+}
+
+/// Re-types the value according to the field definition, the same way loaded values are.
+fn checked_field_value(
+    field_def: &FieldDefinition,
+    value: FieldValue,
+) -> Result<FieldValue, LevelBuilderError> {
+    serde_json::to_value(&value)
+        .ok()
+        .and_then(|json| field_value_from_json(&field_def.field_definition_type, &json).ok())
+        .ok_or_else(|| LevelBuilderError::InvalidFieldValue {
+            field: field_def.identifier.clone(),
+            field_type: field_def.field_definition_type.clone(),
+        })
+}
+
+/// The value LDtk gives new instances of a field: its default override, or a null-like value.
+pub(crate) fn default_field_value(field_def: &FieldDefinition) -> FieldValue {
+    let field_type = field_def.field_definition_type.as_str();
+
+    let default_override = field_def
+        .default_override
+        .as_ref()
+        .and_then(|d| d.get("params"))
+        .and_then(|p| p.get(0))
+        .filter(|_| !field_def.is_array)
+        .map(|param| match (field_type, param.as_i64()) {
+            ("Color", Some(rgb)) => json!(format!("#{:06X}", rgb)),
+            _ => param.clone(),
+        });
+
+    let empty = if field_def.is_array {
+        json!([])
+    } else {
+        Value::Null
+    };
+
+    default_override
+        .into_iter()
+        .chain([empty, json!(false), json!("#000000")])
+        .find_map(|json| field_value_from_json(field_type, &json).ok())
+        .unwrap_or(FieldValue::Unknown(Value::Null))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assets::parse_ldtk_json, validation::validate_level};
+
+    fn platformer() -> LdtkJson {
+        parse_ldtk_json(
+            include_bytes!("../assets/Typical_2D_platformer_example.ldtk"),
+            false,
+        )
+        .unwrap()
+    }
+
+    fn layer<'a>(level: &'a Level, identifier: &str) -> &'a LayerInstance {
+        level
+            .layer_instances
+            .iter()
+            .flatten()
+            .find(|l| l.identifier == identifier)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_built_level_has_every_layer_and_validates() {
+        let project = platformer();
+        let mut builder = LevelBuilder::new(&project.defs, 1000, "Generated", 160, 96);
+        builder
+            .set_int_grid_value("Collisions", IVec2::new(2, 5), 1)
+            .unwrap()
+            .add_entity("Entities", "Player", IVec2::new(3, 4), &[])
+            .unwrap();
+        let level = builder.build();
+
+        assert_eq!(
+            level.layer_instances.as_ref().unwrap().len(),
+            project.defs.layers.len()
+        );
+
+        let collisions = layer(&level, "Collisions");
+        assert_eq!((collisions.c_wid, collisions.c_hei), (10, 6));
+        assert_eq!(collisions.int_grid_csv[5 * 10 + 2], 1);
+        assert_eq!(
+            collisions.int_grid_csv.iter().filter(|v| **v != 0).count(),
+            1
+        );
+
+        assert!(validate_level(&project, &level).is_empty());
+    }
+
+    #[test]
+    fn test_entities_are_placed_like_ldtk() {
+        let project = platformer();
+        let original = project.levels[0]
+            .layer_instances
+            .iter()
+            .flatten()
+            .flat_map(|l| l.entity_instances.iter())
+            .find(|e| e.identifier == "Mob")
+            .unwrap();
+
+        let patrol = |entity: &EntityInstance| {
+            entity
+                .field_instances
+                .iter()
+                .find(|f| f.identifier == "patrol")
+                .map(|f| f.value.clone())
+                .unwrap()
+        };
+
+        let mut builder = LevelBuilder::new(&project.defs, 1000, "Generated", 848, 336);
+        builder
+            .add_entity(
+                "Entities",
+                "Mob",
+                original.grid,
+                &[("patrol", patrol(original))],
+            )
+            .unwrap();
+        let level = builder.build();
+        let mob = &layer(&level, "Entities").entity_instances[0];
+
+        assert_eq!(mob.px, original.px);
+        assert_eq!(mob.pivot, original.pivot);
+        assert_eq!(mob.tile, original.tile);
+        assert_eq!(patrol(mob), patrol(original));
+        assert_eq!(mob.field_instances.len(), original.field_instances.len());
+    }
+
+    #[test]
+    fn test_invalid_operations_are_errors() {
+        let project = platformer();
+        let mut builder = LevelBuilder::new(&project.defs, 1000, "Generated", 160, 96);
+
+        assert_eq!(
+            builder
+                .set_int_grid_value("Entities", IVec2::ZERO, 1)
+                .unwrap_err(),
+            LevelBuilderError::WrongLayerType {
+                layer: "Entities".to_string(),
+                expected: Type::IntGrid,
+                actual: Type::Entities,
+            }
+        );
+        assert_eq!(
+            builder
+                .set_int_grid_value("Collisions", IVec2::new(10, 0), 1)
+                .unwrap_err(),
+            LevelBuilderError::OutOfBounds {
+                layer: "Collisions".to_string(),
+                grid_coords: IVec2::new(10, 0),
+            }
+        );
+        assert_eq!(
+            builder
+                .set_int_grid_value("Collisions", IVec2::ZERO, 99)
+                .unwrap_err(),
+            LevelBuilderError::UnknownIntGridValue {
+                layer: "Collisions".to_string(),
+                value: 99,
+            }
+        );
+        assert_eq!(
+            builder
+                .add_entity("Entities", "Dragon", IVec2::ZERO, &[])
+                .unwrap_err(),
+            LevelBuilderError::UnknownEntity("Dragon".to_string())
+        );
+        assert_eq!(
+            builder
+                .add_entity(
+                    "Entities",
+                    "Mob",
+                    IVec2::ZERO,
+                    &[("patrol", FieldValue::Bool(true))]
+                )
+                .unwrap_err(),
+            LevelBuilderError::InvalidFieldValue {
+                field: "patrol".to_string(),
+                field_type: "Array<Point>".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_tiles_outside_of_tileset_are_errors() {
+        let project = parse_ldtk_json(include_bytes!("../assets/my_project.ldtk"), false).unwrap();
+        let mut builder = LevelBuilder::new(&project.defs, 1000, "Generated", 256, 256);
+
+        // The tileset of the "Tiles" layer is 23x21 tiles
+        for tile_id in [-1, 23 * 21] {
+            assert_eq!(
+                builder.add_tile("Tiles", IVec2::ZERO, tile_id).unwrap_err(),
+                LevelBuilderError::UnknownTile {
+                    layer: "Tiles".to_string(),
+                    tile_id,
+                }
+            );
+        }
+
+        builder
+            .add_flipped_tile("Tiles", IVec2::ZERO, 23 * 21 - 1, true, false)
+            .unwrap();
+        let level = builder.build();
+
+        let tile = &layer(&level, "Tiles").grid_tiles[0];
+        assert_eq!(tile.t, 23 * 21 - 1);
+        assert_eq!(tile.f, 1);
+    }
+
+    #[test]
+    fn test_composed_level_contains_moved_templates() {
+        let project = platformer();
+
+        let mut builder = LevelBuilder::new(&project.defs, 1000, "Room", 64, 48);
+        builder
+            .set_int_grid_value("Collisions", IVec2::new(0, 2), 1)
+            .unwrap()
+            .add_entity(
+                "Entities",
+                "Mob",
+                IVec2::new(1, 1),
+                &[("patrol", FieldValue::Points(vec![Some(IVec2::new(3, 1))]))],
+            )
+            .unwrap();
+        let room = builder.build();
+
+        let mut composer = LevelComposer::new(&project.defs, 16);
+        composer
+            .add_template(&room, IVec2::ZERO)
+            .add_template(&room, IVec2::new(4, 3));
+        let level = composer.compose(2000, "Dungeon").unwrap();
+
+        assert_eq!((level.px_wid, level.px_hei), (128, 96));
+        assert!(validate_level(&project, &level).is_empty());
+
+        let collisions = layer(&level, "Collisions");
+        let cells: Vec<usize> = (0..collisions.int_grid_csv.len())
+            .filter(|i| collisions.int_grid_csv[*i] != 0)
+            .collect();
+        assert_eq!(cells, vec![2 * 8, 5 * 8 + 4]);
+
+        let mobs = &layer(&level, "Entities").entity_instances;
+        assert_eq!(mobs.len(), 2);
+        assert_eq!(mobs[1].grid, IVec2::new(5, 4));
+        assert_eq!(mobs[1].px, mobs[0].px + IVec2::new(64, 48));
+
+        let patrol = mobs[1]
+            .field_instances
+            .iter()
+            .find(|f| f.identifier == "patrol")
+            .unwrap();
+        assert_eq!(
+            patrol.value,
+            FieldValue::Points(vec![Some(IVec2::new(7, 4))])
+        );
+    }
+
+    #[test]
+    fn test_composed_tiles_are_moved() {
+        let project = platformer();
+        let template = &project.levels[0];
+
+        let mut composer = LevelComposer::new(&project.defs, 16);
+        composer.add_template(template, IVec2::new(2, 1));
+        let level = composer.compose(2000, "Shifted").unwrap();
+
+        assert!(validate_level(&project, &level).is_empty());
+
+        let original_tiles = &layer(template, "Bg_textures").auto_layer_tiles;
+        let moved_tiles = &layer(&level, "Bg_textures").auto_layer_tiles;
+        assert_eq!(original_tiles.len(), moved_tiles.len());
+
+        let c_wid = layer(&level, "Bg_textures").c_wid;
+        for (original, moved) in original_tiles.iter().zip(moved_tiles) {
+            assert_eq!(moved.px, original.px + IVec2::new(32, 16));
+            assert_eq!(moved.src, original.src);
+            assert_eq!(
+                *moved.d.last().unwrap(),
+                moved.px.y / 16 * c_wid + moved.px.x / 16
+            );
+        }
+    }
+
+    #[test]
+    fn test_misplaced_templates_are_errors() {
+        let project = platformer();
+        let template = &project.levels[0];
+
+        let mut composer = LevelComposer::new(&project.defs, 16);
+        composer.add_template(template, IVec2::new(-1, 0));
+        assert_eq!(
+            composer.compose(2000, "Negative").unwrap_err(),
+            LevelBuilderError::NegativeOffset(IVec2::new(-1, 0))
+        );
+
+        let mut composer = LevelComposer::new(&project.defs, 8);
+        composer.add_template(template, IVec2::new(1, 0));
+        assert!(matches!(
+            composer.compose(2000, "Misaligned").unwrap_err(),
+            LevelBuilderError::MisalignedOffset { .. }
+        ));
+
+        // Like a level of a project with external levels
+        let external_template = Level {
+            layer_instances: None,
+            ..template.clone()
+        };
+        let mut composer = LevelComposer::new(&project.defs, 16);
+        composer.add_template(&external_template, IVec2::ZERO);
+        assert_eq!(
+            composer.compose(2000, "External").unwrap_err(),
+            LevelBuilderError::MissingLayerInstances(template.uid)
+        );
+    }
+}
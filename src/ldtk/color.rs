@@ -3,8 +3,12 @@ use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 pub(crate) fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
     let color = color.as_rgba_f32();
-    let mut hex_string =
-        hex::encode_upper::<Vec<u8>>(color[0..3].iter().map(|f| (f * 256.) as u8).collect());
+    let mut hex_string = hex::encode_upper::<Vec<u8>>(
+        color[0..3]
+            .iter()
+            .map(|f| (f * 255.).round() as u8)
+            .collect(),
+    );
     hex_string.insert(0, '#');
     hex_string.serialize(serializer)
 }
@@ -1,7 +1,10 @@
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::Cell;
 
 #[allow(unused_imports)]
 use super::{EntityInstance, Level};
+#[allow(unused_imports)]
+use crate::{assets::LdtkLoadError, resources::LdtkSettings};
 use bevy::prelude::*;
 use regex::Regex;
 
@@ -64,82 +67,17 @@ impl<'de> Deserialize<'de> for FieldInstance {
     {
         let helper = FieldInstanceHelper::deserialize(deserializer)?;
 
-        let value = match helper.field_instance_type.as_str() {
-            "Int" => FieldValue::Int(
-                Option::<i32>::deserialize(helper.value).map_err(de::Error::custom)?,
-            ),
-            "Float" => FieldValue::Float(
-                Option::<f32>::deserialize(helper.value).map_err(de::Error::custom)?,
-            ),
-            "Bool" => FieldValue::Bool(bool::deserialize(helper.value).map_err(de::Error::custom)?),
-            "String" => FieldValue::String(
-                Option::<String>::deserialize(helper.value).map_err(de::Error::custom)?,
-            ),
-            "Color" => {
-                let value = color::deserialize(helper.value).map_err(de::Error::custom)?;
-
-                FieldValue::Color(value)
-            }
-            "FilePath" => FieldValue::FilePath(
-                Option::<String>::deserialize(helper.value).map_err(de::Error::custom)?,
-            ),
-            "Point" => {
-                let point_helper =
-                    Option::<PointHelper>::deserialize(helper.value).map_err(de::Error::custom)?;
-
-                FieldValue::Point(point_helper.map(|p| IVec2::new(p.cx, p.cy)))
-            }
-            "Array<Int>" => FieldValue::Ints(
-                Vec::<Option<i32>>::deserialize(helper.value).map_err(de::Error::custom)?,
-            ),
-            "Array<Float>" => FieldValue::Floats(
-                Vec::<Option<f32>>::deserialize(helper.value).map_err(de::Error::custom)?,
-            ),
-            "Array<Bool>" => FieldValue::Bools(
-                Vec::<bool>::deserialize(helper.value).map_err(de::Error::custom)?,
-            ),
-            "Array<String>" => FieldValue::Strings(
-                Vec::<Option<String>>::deserialize(helper.value).map_err(de::Error::custom)?,
-            ),
-            "Array<Color>" => {
-                let helpers =
-                    Vec::<ColorHelper>::deserialize(helper.value).map_err(de::Error::custom)?;
-
-                FieldValue::Colors(helpers.iter().map(|h| h.0).collect())
-            }
-            "Array<FilePath>" => FieldValue::Strings(
-                Vec::<Option<String>>::deserialize(helper.value).map_err(de::Error::custom)?,
-            ),
-            "Array<Point>" => {
-                let point_helpers = Vec::<Option<PointHelper>>::deserialize(helper.value)
-                    .map_err(de::Error::custom)?;
-
-                let points = point_helpers
-                    .into_iter()
-                    .map(|ph| ph.map(|p| IVec2::new(p.cx, p.cy)))
-                    .collect();
-
-                FieldValue::Points(points)
-            }
-            t => {
-                let enum_regex =
-                    Regex::new(r"^(LocalEnum|ExternEnum)\.").expect("enum regex should be valid");
-                let enums_regex = Regex::new(r"^Array<(LocalEnum|ExternEnum)\.")
-                    .expect("enums regex should be valid");
-
-                if enum_regex.is_match(t) {
-                    FieldValue::Enum(
-                        Option::<String>::deserialize(helper.value).map_err(de::Error::custom)?,
-                    )
-                } else if enums_regex.is_match(t) {
-                    FieldValue::Enums(
-                        Vec::<Option<String>>::deserialize(helper.value)
-                            .map_err(de::Error::custom)?,
-                    )
-                } else {
-                    return Err(de::Error::custom("Encountered unknown field type"));
-                }
+        // Fields that can't be re-typed are only kept as they are when deserializing leniently.
+        let value = match field_value_from_json(&helper.field_instance_type, &helper.value) {
+            Ok(value) => value,
+            Err(_) if LENIENT.with(Cell::get) => FieldValue::Unknown(helper.value),
+            Err(FieldValueError::UnknownType) => {
+                return Err(de::Error::custom(format!(
+                    "unknown field type {}",
+                    helper.field_instance_type
+                )))
             }
+            Err(FieldValueError::InvalidValue(e)) => return Err(de::Error::custom(e)),
         };
 
         Ok(FieldInstance {
@@ -152,6 +90,99 @@ impl<'de> Deserialize<'de> for FieldInstance {
     }
 }
 
+thread_local! {
+    static LENIENT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` with [FieldInstance]s deserializing fields that can't be re-typed as
+/// [FieldValue::Unknown], instead of failing.
+pub(crate) fn deserialize_leniently<T>(f: impl FnOnce() -> T) -> T {
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            LENIENT.with(|lenient| lenient.set(self.0));
+        }
+    }
+
+    let _reset = Reset(LENIENT.with(|lenient| lenient.replace(true)));
+
+    f()
+}
+
+/// Reasons a field's `__value` can fail to be re-typed as a [FieldValue].
+#[derive(Debug)]
+pub(crate) enum FieldValueError {
+    /// The field's `__type` is not one this plugin knows about.
+    UnknownType,
+    /// The `__value` doesn't match the field's `__type`.
+    InvalidValue(serde_json::Error),
+}
+
+impl From<serde_json::Error> for FieldValueError {
+    fn from(e: serde_json::Error) -> Self {
+        FieldValueError::InvalidValue(e)
+    }
+}
+
+/// Re-types the `__value` of a field instance according to its `__type`.
+pub(crate) fn field_value_from_json(
+    field_type: &str,
+    value: &serde_json::Value,
+) -> Result<FieldValue, FieldValueError> {
+    Ok(match field_type {
+        "Int" => FieldValue::Int(Option::<i32>::deserialize(value)?),
+        "Float" => FieldValue::Float(Option::<f32>::deserialize(value)?),
+        "Bool" => FieldValue::Bool(bool::deserialize(value)?),
+        "String" => FieldValue::String(Option::<String>::deserialize(value)?),
+        "Color" => {
+            let value = color::deserialize(value)?;
+
+            FieldValue::Color(value)
+        }
+        "FilePath" => FieldValue::FilePath(Option::<String>::deserialize(value)?),
+        "Point" => {
+            let point_helper = Option::<PointHelper>::deserialize(value)?;
+
+            FieldValue::Point(point_helper.map(|p| IVec2::new(p.cx, p.cy)))
+        }
+        "Array<Int>" => FieldValue::Ints(Vec::<Option<i32>>::deserialize(value)?),
+        "Array<Float>" => FieldValue::Floats(Vec::<Option<f32>>::deserialize(value)?),
+        "Array<Bool>" => FieldValue::Bools(Vec::<bool>::deserialize(value)?),
+        "Array<String>" => FieldValue::Strings(Vec::<Option<String>>::deserialize(value)?),
+        "Array<Color>" => {
+            let helpers = Vec::<ColorHelper>::deserialize(value)?;
+
+            FieldValue::Colors(helpers.iter().map(|h| h.0).collect())
+        }
+        "Array<FilePath>" => FieldValue::FilePaths(Vec::<Option<String>>::deserialize(value)?),
+        "Array<Point>" => {
+            let point_helpers = Vec::<Option<PointHelper>>::deserialize(value)?;
+
+            let points = point_helpers
+                .into_iter()
+                .map(|ph| ph.map(|p| IVec2::new(p.cx, p.cy)))
+                .collect();
+
+            FieldValue::Points(points)
+        }
+        t => {
+            let enum_regex =
+                Regex::new(r"^(LocalEnum|ExternEnum)\.").expect("enum regex should be valid");
+            let enums_regex = Regex::new(r"^Array<(LocalEnum|ExternEnum)\.")
+                .expect("enums regex should be valid");
+
+            if enum_regex.is_match(t) {
+                FieldValue::Enum(Option::<String>::deserialize(value)?)
+            } else if enums_regex.is_match(t) {
+                FieldValue::Enums(Vec::<Option<String>>::deserialize(value)?)
+            } else {
+                return Err(FieldValueError::UnknownType);
+            }
+        }
+    })
+}
+
 #[derive(PartialEq, Debug, Clone, Serialize)]
 #[serde(untagged)]
 /// The actual value of a field instance on a [Level] or [EntityInstance].
@@ -182,6 +213,14 @@ pub enum FieldValue {
     Enums(Vec<Option<String>>),
     #[serde(serialize_with = "serialize_points")]
     Points(Vec<Option<IVec2>>),
+    /// The raw value of a field whose type is unknown to this plugin, or whose value doesn't
+    /// match its type.
+    ///
+    /// These are only deserialized by the lenient parsers, like the
+    /// [LdtkLoader](crate::assets::LdtkLoader) with [LdtkSettings::lenient_loading].
+    /// Otherwise, such fields fail to deserialize, and projects fail to load with an
+    /// [LdtkLoadError].
+    Unknown(serde_json::Value),
 }
 
 fn serialize_colors<S: Serializer>(colors: &[Color], serializer: S) -> Result<S::Ok, S::Error> {
@@ -201,3 +240,132 @@ fn serialize_points<S: Serializer>(
     let field_values: Vec<FieldValue> = points.iter().map(|p| FieldValue::Point(*p)).collect();
     field_values.serialize(serializer)
 }
+
+/// Creates the `realEditorValues` LDtk reads a field instance's value from, since LDtk ignores
+/// `__value` when opening a project.
+///
+/// Values of [FieldValue::Unknown] can't be converted, so they have no editor values.
+pub(crate) fn real_editor_values(value: &FieldValue) -> Vec<Option<serde_json::Value>> {
+    fn editor_value(id: &str, param: serde_json::Value) -> Option<serde_json::Value> {
+        Some(serde_json::json!({ "id": id, "params": [param] }))
+    }
+
+    fn color_value(color: &Color) -> Option<serde_json::Value> {
+        let rgb = color.as_rgba_f32()[0..3]
+            .iter()
+            .fold(0, |rgb, f| (rgb << 8) | (f * 255.).round() as i32);
+        editor_value("V_Int", rgb.into())
+    }
+
+    fn float_value(float: &Option<f32>) -> Option<serde_json::Value> {
+        // Going through the shortest decimal representation keeps `10.8` from becoming
+        // `10.800000190734863` as an f64.
+        float
+            .and_then(|f| f.to_string().parse::<f64>().ok())
+            .and_then(|f| editor_value("V_Float", f.into()))
+    }
+
+    fn point_value(point: &Option<IVec2>) -> Option<serde_json::Value> {
+        point.and_then(|p| editor_value("V_String", format!("{},{}", p.x, p.y).into()))
+    }
+
+    fn string_value(string: &Option<String>) -> Option<serde_json::Value> {
+        string
+            .as_ref()
+            .and_then(|s| editor_value("V_String", s.clone().into()))
+    }
+
+    let single_value = match value {
+        FieldValue::Int(i) => i.and_then(|i| editor_value("V_Int", i.into())),
+        FieldValue::Float(f) => float_value(f),
+        FieldValue::Bool(b) => editor_value("V_Bool", (*b).into()),
+        FieldValue::String(s) | FieldValue::FilePath(s) | FieldValue::Enum(s) => string_value(s),
+        FieldValue::Color(c) => color_value(c),
+        FieldValue::Point(p) => point_value(p),
+        FieldValue::Ints(ints) => {
+            return ints
+                .iter()
+                .map(|i| i.and_then(|i| editor_value("V_Int", i.into())))
+                .collect()
+        }
+        FieldValue::Floats(floats) => return floats.iter().map(float_value).collect(),
+        FieldValue::Bools(bools) => {
+            return bools
+                .iter()
+                .map(|b| editor_value("V_Bool", (*b).into()))
+                .collect()
+        }
+        FieldValue::Strings(strings)
+        | FieldValue::FilePaths(strings)
+        | FieldValue::Enums(strings) => return strings.iter().map(string_value).collect(),
+        FieldValue::Colors(colors) => return colors.iter().map(color_value).collect(),
+        FieldValue::Points(points) => return points.iter().map(point_value).collect(),
+        FieldValue::Unknown(_) => None,
+    };
+
+    // Unset single values have no editor values at all, rather than a null one.
+    single_value.into_iter().map(Some).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ldtk::LdtkJson;
+
+    #[test]
+    fn test_real_editor_values_match_ldtk() {
+        let project: LdtkJson =
+            serde_json::from_str(include_str!("../../assets/field_instances.ldtk")).unwrap();
+
+        let field_instances = project
+            .levels
+            .iter()
+            .flat_map(|level| level.layer_instances.iter().flatten())
+            .flat_map(|layer| layer.entity_instances.iter())
+            .flat_map(|entity| entity.field_instances.iter());
+
+        // LDtk leaves out values that are equal to the field's default or null, while they are
+        // always written here unless null.
+        for field_instance in field_instances.filter(|f| !f.real_editor_values.is_empty()) {
+            let real_editor_values = real_editor_values(&field_instance.value);
+            assert_eq!(
+                real_editor_values.len(),
+                field_instance.real_editor_values.len()
+            );
+
+            for (actual, expected) in real_editor_values
+                .iter()
+                .zip(&field_instance.real_editor_values)
+                .filter(|(_, expected)| {
+                    expected
+                        .as_ref()
+                        .map(|e| !e["params"][0].is_null())
+                        .unwrap_or(false)
+                })
+            {
+                assert_eq!(actual, expected, "{}", field_instance.identifier);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_fields_are_only_deserialized_leniently() {
+        let field = serde_json::json!({
+            "__identifier": "Destination",
+            "__type": "Array<Wormhole>",
+            "__value": [1, 2],
+            "defUid": 1,
+            "realEditorValues": [],
+        });
+
+        assert!(FieldInstance::deserialize(&field).is_err());
+
+        let field_instance = deserialize_leniently(|| FieldInstance::deserialize(&field)).unwrap();
+        assert_eq!(
+            field_instance.value,
+            FieldValue::Unknown(serde_json::json!([1, 2]))
+        );
+
+        assert!(FieldInstance::deserialize(&field).is_err());
+    }
+}
@@ -22,6 +22,16 @@
 //!     have been changed from vectors to [IVec2] and [Vec2].
 //! 12. Some "color" fields on [LdtkJson], [EntityDefinition], [IntGridValueDefinition], and
 //!     [Level] have been changed from [String]s to [Color].
+//! 13. The alpha fields of [TileInstance] and [AutoLayerRuleDefinition] from newer LDtk versions
+//!     have been added, defaulting to `1.0` when absent. As a result, [TileInstance] has been
+//!     given a custom [Default] implementation, and no longer derives [Eq].
+//! 14. [Hash] has been derived for [Type], so it can be used to register layer spawners.
+//! 15. The `__header__` field of [LdtkJson] has been added as an untyped [serde_json::Value].
+//! 16. Deprecated [Option] fields absent from newer LDtk versions, and alpha fields equal to
+//!     `1.0`, are skipped when serializing, so that files round-trip without gaining new fields.
+//! 17. [FieldValue::Unknown] has been added for fields that can't be re-typed. It is only
+//!     deserialized by the lenient parsers, like [parse_ldtk_json](crate::parse_ldtk_json)
+//!     with `lenient` set. Otherwise, [FieldInstance] fails to deserialize such fields.
 
 use bevy::prelude::{Color, IVec2, Vec2};
 use serde::{Deserialize, Serialize};
@@ -42,6 +52,10 @@ pub use field_instance::*;
 /// users).
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct LdtkJson {
+    /// LDtk application build identifier, and other information about the file format
+    #[serde(rename = "__header__", skip_serializing_if = "Option::is_none")]
+    pub header: Option<serde_json::Value>,
+
     /// Number of backup files to keep, if the `backupOnSave` is TRUE
     #[serde(rename = "backupLimit")]
     pub backup_limit: i32,
@@ -84,7 +98,7 @@ pub struct LdtkJson {
 
     /// **WARNING**: this deprecated value is no longer exported since version 0.9.3  Replaced
     /// by: `imageExportMode`
-    #[serde(rename = "exportPng")]
+    #[serde(rename = "exportPng", skip_serializing_if = "Option::is_none")]
     pub export_png: Option<bool>,
 
     /// If TRUE, a Tiled compatible file will also be generated along with the LDtk JSON file
@@ -515,6 +529,14 @@ pub struct AutoLayerRuleDefinition {
     #[serde(rename = "active")]
     pub active: bool,
 
+    /// Opacity of the tiles generated by this rule (0 to 1)
+    #[serde(
+        rename = "alpha",
+        default = "default_alpha",
+        skip_serializing_if = "is_default_alpha"
+    )]
+    pub alpha: f32,
+
     /// When TRUE, the rule will prevent other rules to be applied in the same cell if it matches
     /// (TRUE by default).
     #[serde(rename = "breakOnMatch")]
@@ -843,7 +865,7 @@ pub struct LayerInstance {
 
     /// **WARNING**: this deprecated value will be *removed* completely on version 0.10.0+
     /// Replaced by: `intGridCsv`
-    #[serde(rename = "intGrid")]
+    #[serde(rename = "intGrid", skip_serializing_if = "Option::is_none")]
     pub int_grid: Option<Vec<IntGridValueInstance>>,
 
     /// A list of all values in the IntGrid layer, stored from left to right, and top to bottom
@@ -889,8 +911,16 @@ pub struct LayerInstance {
 }
 
 /// This structure represents a single tile from a given Tileset.
-#[derive(Eq, PartialEq, Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct TileInstance {
+    /// Alpha/opacity of the tile (0-1, defaults to 1)
+    #[serde(
+        rename = "a",
+        default = "default_alpha",
+        skip_serializing_if = "is_default_alpha"
+    )]
+    pub a: f32,
+
     /// Internal data used by the editor.<br/>  For auto-layer tiles: `[ruleId, coordId]`.<br/>
     /// For tile-layer tiles: `[coordId]`.
     #[serde(rename = "d")]
@@ -916,6 +946,27 @@ pub struct TileInstance {
     pub t: i32,
 }
 
+impl Default for TileInstance {
+    fn default() -> Self {
+        TileInstance {
+            a: default_alpha(),
+            d: Vec::new(),
+            f: 0,
+            px: IVec2::ZERO,
+            src: IVec2::ZERO,
+            t: 0,
+        }
+    }
+}
+
+fn default_alpha() -> f32 {
+    1.
+}
+
+fn is_default_alpha(alpha: &f32) -> bool {
+    *alpha == default_alpha()
+}
+
 /// Component added to any LDtk Entity by default.
 ///
 /// When loading levels, you can flesh out LDtk entities in your own system by querying for
@@ -1187,7 +1238,7 @@ pub enum TileMode {
 
 /// Type of the layer as Haxe Enum Possible values: `IntGrid`, `Entities`, `Tiles`,
 /// `AutoLayer`
-#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[derive(Eq, PartialEq, Debug, Clone, Hash, Serialize, Deserialize)]
 pub enum Type {
     #[serde(rename = "AutoLayer")]
     AutoLayer,
@@ -1256,3 +1307,115 @@ pub enum WorldLayout {
     #[serde(rename = "LinearVertical")]
     LinearVertical,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    /// Compares JSON values, treating numbers as equal if they have the same value, since `1` may
+    /// be re-serialized as `1.0`.
+    fn assert_json_eq(original: &Value, round_tripped: &Value, path: &str) {
+        match (original, round_tripped) {
+            (Value::Number(a), Value::Number(b)) => {
+                let (a, b) = (a.as_f64().unwrap(), b.as_f64().unwrap());
+                assert!(
+                    (a - b).abs() <= f32::EPSILON as f64 * a.abs().max(1.),
+                    "{}: {} != {}",
+                    path,
+                    a,
+                    b
+                );
+            }
+            (Value::Array(a), Value::Array(b)) => {
+                assert_eq!(a.len(), b.len(), "{}: array lengths differ", path);
+                for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                    assert_json_eq(a, b, &format!("{}[{}]", path, i));
+                }
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                for key in a.keys().chain(b.keys()) {
+                    match (a.get(key), b.get(key)) {
+                        (Some(a), Some(b)) => assert_json_eq(a, b, &format!("{}.{}", path, key)),
+                        _ => panic!("{}.{}: field only present on one side", path, key),
+                    }
+                }
+            }
+            _ => assert_eq!(original, round_tripped, "{}", path),
+        }
+    }
+
+    fn assert_round_trips(file: &str) {
+        let original: Value = serde_json::from_str(file).unwrap();
+        let project: LdtkJson = serde_json::from_str(file).unwrap();
+
+        let serialized = serde_json::to_string(&project).unwrap();
+        assert_json_eq(&original, &serde_json::from_str(&serialized).unwrap(), "");
+
+        let reparsed: LdtkJson = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(project, reparsed);
+
+        for (level, original_level) in project
+            .levels
+            .iter()
+            .zip(original["levels"].as_array().unwrap())
+        {
+            let serialized = serde_json::to_value(level).unwrap();
+            assert_json_eq(original_level, &serialized, &level.identifier);
+        }
+    }
+
+    /// Like [assert_round_trips], but for the contents of an external level file.
+    fn assert_level_round_trips(file: &str) {
+        let original: Value = serde_json::from_str(file).unwrap();
+        let level: Level = serde_json::from_str(file).unwrap();
+
+        let serialized = serde_json::to_string(&level).unwrap();
+        assert_json_eq(
+            &original,
+            &serde_json::from_str(&serialized).unwrap(),
+            &level.identifier,
+        );
+
+        let reparsed: Level = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(level, reparsed);
+    }
+
+    #[test]
+    fn test_platformer_round_trips() {
+        assert_round_trips(include_str!(
+            "../../assets/Typical_2D_platformer_example.ldtk"
+        ));
+    }
+
+    #[test]
+    fn test_world_map_round_trips() {
+        assert_round_trips(include_str!("../../assets/WorldMap_Free_layout.ldtk"));
+    }
+
+    #[test]
+    fn test_field_instances_round_trip() {
+        assert_round_trips(include_str!("../../assets/field_instances.ldtk"));
+    }
+
+    #[test]
+    fn test_my_project_round_trips() {
+        assert_round_trips(include_str!("../../assets/my_project.ldtk"));
+    }
+
+    #[test]
+    fn test_external_levels_round_trip() {
+        // LDtk saves each external level file (.ldtkl) as the complete level object, so they are
+        // made here from the levels of a project.
+        for file in [
+            include_str!("../../assets/WorldMap_Free_layout.ldtk"),
+            include_str!("../../assets/field_instances.ldtk"),
+        ] {
+            let project: Value = serde_json::from_str(file).unwrap();
+
+            for level in project["levels"].as_array().unwrap() {
+                assert_level_round_trips(&serde_json::to_string_pretty(level).unwrap());
+            }
+        }
+    }
+}
@@ -71,7 +71,9 @@
 //! Regardless of your choice, the spawned entities will have an appropriate [Transform].
 //! They will also be spawned and despawned along with the levels they belong to, unless otherwise
 //! specified with a [Worldly] component.
-//! This is because, by default, the entities are spawned as children of the level entities.
+//! This is because, by default, the entities are spawned as descendants of the level entities.
+//! Each LDtk layer is spawned as a child of its level with an [LdtkLayer] component, and the
+//! layer's tiles and entities are spawned as its children.
 //!
 //! ### Worlds and Levels
 //!
@@ -102,11 +104,15 @@ use bevy_ecs_tilemap::prelude::*;
 pub mod app;
 mod assets;
 mod components;
+pub mod editor;
+pub mod export;
+pub mod generation;
 pub mod ldtk;
 mod resources;
 pub mod systems;
 mod tile_makers;
 pub mod utils;
+pub mod validation;
 
 pub use assets::*;
 pub use components::*;
@@ -139,19 +145,59 @@ mod plugin {
 
     impl Plugin for LdtkPlugin {
         fn build(&self, app: &mut App) {
+            // The loaders are configured with the settings present when the plugin is added.
+            let ldtk_settings = *app
+                .world
+                .get_resource_or_insert_with(resources::LdtkSettings::default);
+
+            let load_errors = resources::LdtkLoadErrorQueue::default();
+            let external_level_projects = assets::ExternalLevelProjects::default();
+
             app.add_plugin(TilemapPlugin)
                 .init_non_send_resource::<app::LdtkEntityMap>()
                 .init_non_send_resource::<app::LdtkIntCellMap>()
-                .init_resource::<resources::LdtkSettings>()
+                .init_non_send_resource::<app::LdtkLayerSpawnerMap>()
+                .init_resource::<resources::WhiteImages>()
+                .init_resource::<resources::LdtkProjectHashes>()
+                .init_resource::<resources::LevelMapIds>()
+                .init_resource::<resources::TilesetImages>()
+                .insert_resource(load_errors.clone())
                 .add_asset::<assets::LdtkAsset>()
-                .init_asset_loader::<assets::LdtkLoader>()
+                .add_asset_loader(assets::LdtkLoader {
+                    external_level_loading: ldtk_settings.external_level_loading,
+                    lenient_loading: ldtk_settings.lenient_loading,
+                    load_errors: load_errors.clone(),
+                    external_level_projects: external_level_projects.clone(),
+                })
                 .add_asset::<assets::LdtkLevel>()
-                .init_asset_loader::<assets::LdtkLevelLoader>()
+                .add_asset_loader(assets::LdtkLevelLoader {
+                    lenient_loading: ldtk_settings.lenient_loading,
+                    load_errors,
+                    external_level_projects,
+                })
                 .add_event::<resources::LevelEvent>()
+                .add_event::<resources::LdtkWorldEvent>()
+                .add_event::<resources::LdtkLoadErrorEvent>()
                 .add_system_to_stage(
                     CoreStage::PreUpdate,
                     systems::process_ldtk_world.label(LdtkSystemLabel::PreSpawn),
                 )
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    systems::process_ldtk_level_reloads.label(LdtkSystemLabel::PreSpawn),
+                )
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    systems::trigger_loaded_level_spawns.label(LdtkSystemLabel::PreSpawn),
+                )
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    systems::fire_load_error_events.label(LdtkSystemLabel::Other),
+                )
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    systems::check_registrations.label(LdtkSystemLabel::Other),
+                )
                 .add_system_to_stage(
                     CoreStage::PreUpdate,
                     systems::choose_levels.label(LdtkSystemLabel::LevelSelection),
@@ -164,7 +210,9 @@ mod plugin {
                 )
                 .add_system_to_stage(
                     CoreStage::PreUpdate,
-                    systems::set_ldtk_texture_filters_to_nearest.label(LdtkSystemLabel::Other),
+                    systems::set_ldtk_texture_filters_to_nearest
+                        .label(LdtkSystemLabel::Other)
+                        .after(LdtkSystemLabel::PreSpawn),
                 )
                 .add_system_to_stage(
                     CoreStage::PreUpdate,
@@ -180,6 +228,12 @@ mod plugin {
                 .add_system_to_stage(
                     CoreStage::PostUpdate,
                     systems::process_ldtk_levels.label(LdtkSystemLabel::LevelSpawning),
+                )
+                .add_system_to_stage(
+                    CoreStage::PostUpdate,
+                    systems::update_load_progress
+                        .label(LdtkSystemLabel::Other)
+                        .after(bevy::transform::TransformSystem::TransformPropagate),
                 );
         }
     }
@@ -191,10 +245,16 @@ pub mod prelude {
     pub use crate::{
         app::{LdtkEntity, LdtkIntCell, RegisterLdtkObjects},
         assets::{LdtkAsset, LdtkLevel},
-        components::{EntityInstance, GridCoords, IntGridCell, LdtkWorldBundle, LevelSet, Worldly},
+        components::{
+            EntityInstance, GridCoords, IntGrid, IntGridCell, LdtkLayer, LdtkLayerQuery,
+            LdtkLoadProgress, LdtkWorldBundle, LevelSet, LevelUid, Worldly,
+        },
         ldtk::{self, FieldValue, LayerInstance, TilesetDefinition},
         plugin::LdtkPlugin,
-        resources::{LdtkSettings, LevelEvent, LevelSelection},
+        resources::{
+            ExternalLevelLoading, IntGridMode, IntGridStorage, LdtkLoadErrorEvent, LdtkSettings,
+            LdtkWorldEvent, LevelEvent, LevelSelection,
+        },
     };
 
     #[cfg(feature = "derive")]
@@ -1,12 +1,30 @@
 //! Resources and events used by the plugin.
 
-use crate::ldtk::Level;
+use crate::{
+    assets::{LdtkAsset, LdtkLoadError},
+    ldtk::{LdtkJson, Level},
+    utils::hash_serialized,
+};
+use bevy::{
+    asset::LoadContext,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
 #[allow(unused_imports)]
-use bevy::prelude::GlobalTransform;
-
-#[allow(unused_imports)]
-use crate::components::{LdtkWorldBundle, LevelSet};
+use crate::{
+    app::{IntGridLayerSpawner, LdtkEntity, LdtkIntCell},
+    assets::LdtkLevel,
+    components::{
+        IntGrid, IntGridCell, LdtkLayer, LdtkLoadProgress, LdtkWorldBundle, LevelSet, Worldly,
+    },
+    validation::validate_registrations,
+};
 
 /// Resource for choosing which level(s) to spawn.
 ///
@@ -42,6 +60,65 @@ impl LevelSelection {
     }
 }
 
+/// Determines what is spawned for IntGrid layers.
+///
+/// Used by [LdtkSettings::int_grid_mode], or per-layer with [IntGridLayerSpawner].
+///
+/// Regardless of this setting, AutoLayer tiles on IntGrid layers are always rendered.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub enum IntGridMode {
+    /// IntGrid values are rendered, and every nonzero cell is spawned as an entity with an
+    /// [IntGridCell] component, or whatever [LdtkIntCell] bundle is registered for it.
+    #[default]
+    Full,
+    /// IntGrid values are not rendered.
+    ///
+    /// Every nonzero cell is still spawned as an entity with an [IntGridCell] component, or
+    /// whatever [LdtkIntCell] bundle is registered for it.
+    /// These are children of an entity with an [LdtkLayer] component, rather than tiles of a
+    /// bevy_ecs_tilemap layer.
+    DataOnly,
+    /// IntGrid values are rendered, but cells are not spawned with [IntGridCell] components or
+    /// [LdtkIntCell] bundles.
+    ///
+    /// This greatly reduces the number of components spawned on large maps.
+    RenderOnly,
+}
+
+/// Determines which IntGrid cells are spawned as entities.
+///
+/// Used by [LdtkSettings::int_grid_storage].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub enum IntGridStorage {
+    /// Every nonzero cell is spawned as an entity with an [IntGridCell] component, or whatever
+    /// [LdtkIntCell] bundle is registered for it.
+    #[default]
+    Entities,
+    /// Only cells matching a registered [LdtkIntCell] bundle are spawned as entities.
+    ///
+    /// The values of every cell are still available through the layer's [IntGrid] component.
+    /// On large maps, this avoids spawning tens of thousands of entities that need transform
+    /// propagation.
+    Compact,
+}
+
+/// Determines when the external level files of a project are loaded.
+///
+/// Used by [LdtkSettings::external_level_loading].
+/// Has no effect on projects that don't use external levels.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub enum ExternalLevelLoading {
+    /// Every external level is loaded as a dependency of the [LdtkAsset], before anything spawns.
+    #[default]
+    Eager,
+    /// External levels are only loaded once they are selected in a [LevelSet], and can be
+    /// unloaded once they despawn.
+    ///
+    /// The [LevelEvent::SpawnTriggered] event for such a level is delayed until its file has
+    /// loaded.
+    Lazy,
+}
+
 /// Settings resource for the plugin.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct LdtkSettings {
@@ -64,6 +141,83 @@ pub struct LdtkSettings {
     ///
     /// Defaults to `true`.
     pub set_clear_color: bool,
+    /// Caps the number of tiles that can be stacked in a single cell of a Tile, AutoTile, or
+    /// IntGrid layer.
+    ///
+    /// Each level of stacking in a layer is spawned as an additional bevy_ecs_tilemap layer.
+    /// If a cell exceeds this limit, only its top-most tiles are kept, and a warning is logged.
+    /// See [crate::utils::layer_grid_tiles] for more details.
+    ///
+    /// Defaults to `None`.
+    pub max_tile_stack_depth: Option<usize>,
+    /// The z value given to the background of each level, and the lowest z value used by its
+    /// layers.
+    ///
+    /// bevy_ecs_tilemap orders its layers by their integer layer ids, so this value is also the
+    /// layer id of the background.
+    /// See [LdtkLayer] for finding the z value of a spawned layer.
+    ///
+    /// Defaults to `0`.
+    pub layer_z_base: u16,
+    /// The minimum difference in z values between one LDtk layer and the LDtk layer beneath it.
+    ///
+    /// Some LDtk layers are spawned as multiple bevy_ecs_tilemap layers, which occupy consecutive
+    /// z values.
+    /// If a layer needs more z values than this step provides, the layers above it are pushed up
+    /// accordingly.
+    /// Increasing this leaves gaps in the z values between layers, where you can place your own
+    /// sprites.
+    ///
+    /// Defaults to `1`.
+    pub layer_z_step: u16,
+    /// Determines whether IntGrid layers are spawned with their values rendered, with an entity
+    /// for each cell, or both.
+    ///
+    /// This can be overridden for particular layers by registering an [IntGridLayerSpawner].
+    ///
+    /// Defaults to [IntGridMode::Full].
+    pub int_grid_mode: IntGridMode,
+    /// Determines whether every nonzero IntGrid cell is spawned as an entity, or just those with
+    /// registered [LdtkIntCell] bundles.
+    ///
+    /// Either way, IntGrid layers spawned with cell data have an [IntGrid] component.
+    ///
+    /// Defaults to [IntGridStorage::Entities].
+    pub int_grid_storage: IntGridStorage,
+    /// When an LDtk project is hot-reloaded, [Worldly] entities are despawned along with the
+    /// levels that changed.
+    /// They are then respawned with their levels.
+    ///
+    /// By default, [Worldly] entities, like a player, keep their state through hot reloads.
+    ///
+    /// Defaults to `false`.
+    pub despawn_worldly_on_reload: bool,
+    /// Determines whether the external level files of a project are loaded along with the
+    /// project, or only when their levels are spawned.
+    ///
+    /// This is read when the plugin is added, so this resource needs to be inserted before
+    /// `LdtkPlugin` for this setting to take effect.
+    ///
+    /// Defaults to [ExternalLevelLoading::Eager].
+    pub external_level_loading: ExternalLevelLoading,
+    /// Loads projects even if some of their data is broken, logging warnings instead of failing.
+    ///
+    /// Fields with unknown types or malformed values are loaded as
+    /// [FieldValue::Unknown](crate::ldtk::FieldValue::Unknown), and levels that still fail to
+    /// load are skipped.
+    ///
+    /// Like [LdtkSettings::external_level_loading], this is read when the plugin is added.
+    ///
+    /// Defaults to `false`.
+    pub lenient_loading: bool,
+    /// Panics in debug builds when an [LdtkEntity] or [LdtkIntCell] is registered for a layer
+    /// identifier, entity identifier, or IntGrid value that doesn't exist in any loaded project.
+    ///
+    /// Such registrations are always reported with warnings, since they usually indicate a typo.
+    /// See [validate_registrations].
+    ///
+    /// Defaults to `false`.
+    pub strict_registrations: bool,
 }
 
 impl Default for LdtkSettings {
@@ -72,6 +226,15 @@ impl Default for LdtkSettings {
             use_level_world_translations: false,
             load_level_neighbors: false,
             set_clear_color: true,
+            max_tile_stack_depth: None,
+            layer_z_base: 0,
+            layer_z_step: 1,
+            int_grid_mode: IntGridMode::Full,
+            int_grid_storage: IntGridStorage::Entities,
+            despawn_worldly_on_reload: false,
+            external_level_loading: ExternalLevelLoading::Eager,
+            lenient_loading: false,
+            strict_registrations: false,
         }
     }
 }
@@ -84,6 +247,8 @@ pub enum LevelEvent {
     /// Indicates that a level has been triggered to spawn, but hasn't been spawned yet.
     ///
     /// Occurs one update before the level is spawned.
+    /// If the level's asset is still loading, as with [ExternalLevelLoading::Lazy], this waits
+    /// until it has loaded.
     SpawnTriggered(i32),
     /// The level, with all of its layers, entities, etc., has spawned.
     ///
@@ -96,4 +261,302 @@ pub enum LevelEvent {
     Transformed(i32),
     /// Indicates that a level has despawned.
     Despawned(i32),
+    /// Indicates that a level is being respawned because its content changed when the LDtk
+    /// project was hot-reloaded.
+    ///
+    /// Occurs after the [LevelEvent::Despawned] event of the old level, and before the
+    /// [LevelEvent::SpawnTriggered] event of the new one.
+    Reloaded(i32),
+}
+
+/// Events fired by the plugin related to the loading of an entire [LdtkWorldBundle].
+///
+/// Each variant stores the world entity.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum LdtkWorldEvent {
+    /// The project, its tilesets, and every level in its [LevelSet] have loaded and spawned.
+    ///
+    /// Like [LevelEvent::Transformed], this occurs after the [GlobalTransform]s of the spawned
+    /// levels have been updated.
+    /// See [LdtkLoadProgress] for the state of worlds that aren't ready yet.
+    Ready(Entity),
+}
+
+/// Event fired when an [LdtkAsset] or [LdtkLevel] file fails to load.
+///
+/// Useful for showing what went wrong in-game, rather than just in the logs.
+#[derive(Debug)]
+pub struct LdtkLoadErrorEvent {
+    /// The asset path of the file that failed to load.
+    pub path: PathBuf,
+    pub error: LdtkLoadError,
+}
+
+/// Resource through which the asset loaders pass [LdtkLoadErrorEvent]s to the app.
+///
+/// Asset loaders run outside of the ECS, so their errors are queued here until they are fired as
+/// events.
+#[derive(Clone, Debug, Default)]
+pub struct LdtkLoadErrorQueue {
+    events: Arc<Mutex<Vec<LdtkLoadErrorEvent>>>,
+}
+
+impl LdtkLoadErrorQueue {
+    /// Queues an [LdtkLoadErrorEvent], returning the error for the asset loader to fail with.
+    pub(crate) fn report(&self, load_context: &LoadContext, error: LdtkLoadError) -> anyhow::Error {
+        let result = anyhow::Error::msg(error.to_string());
+
+        if let Ok(mut events) = self.events.lock() {
+            events.push(LdtkLoadErrorEvent {
+                path: load_context.path().to_path_buf(),
+                error,
+            });
+        }
+
+        result
+    }
+
+    pub(crate) fn drain(&self) -> Vec<LdtkLoadErrorEvent> {
+        self.events
+            .lock()
+            .map(|mut events| events.drain(..).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Resource storing small white images for untextured tilemap layers, shared by all levels.
+///
+/// Without the "atlas" feature, bevy_ecs_tilemap copies each tile of a layer's image into a
+/// texture array, so the image needs to be at least as large as the layer's tiles, and an image
+/// can only be used with one tile size.
+/// So, there is one square image for each tile size used.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct WhiteImages {
+    handles: HashMap<u32, Handle<Image>>,
+}
+
+impl WhiteImages {
+    /// Returns a handle to a white image `size` pixels wide and tall, creating it if necessary.
+    pub(crate) fn get_or_add(&mut self, size: u32, images: &mut Assets<Image>) -> Handle<Image> {
+        self.handles
+            .entry(size)
+            .or_insert_with(|| {
+                let mut white_image = Image::new_fill(
+                    Extent3d {
+                        width: size,
+                        height: size,
+                        depth_or_array_layers: 1,
+                    },
+                    TextureDimension::D2,
+                    &[255, 255, 255, 255],
+                    TextureFormat::Rgba8UnormSrgb,
+                );
+                white_image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_SRC
+                    | TextureUsages::COPY_DST;
+
+                images.add(white_image)
+            })
+            .clone()
+    }
+}
+
+/// Resource tracking the tileset images that the plugin has modified or replaced.
+///
+/// Updating the texture usages of a tileset image sends an [AssetEvent::Modified] event for it,
+/// which shouldn't be mistaken for a hot reload of the tileset.
+///
+/// Without the "atlas" feature, bevy_ecs_tilemap caches a texture array for each tileset image
+/// handle for the lifetime of the app.
+/// So, hot-reloaded tileset images are copied to a new image, and levels are spawned with the
+/// copy instead.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct TilesetImages {
+    usage_updates: HashSet<Handle<Image>>,
+    reloaded: HashMap<Handle<Image>, Handle<Image>>,
+}
+
+impl TilesetImages {
+    /// Records that the usages of the given tileset image were just updated.
+    pub(crate) fn add_usage_update(&mut self, handle: &Handle<Image>) {
+        self.usage_updates.insert(handle.clone_weak());
+    }
+
+    /// Returns true if the modification of the given image was caused by a usage update, rather
+    /// than a hot reload.
+    pub(crate) fn take_usage_update(&mut self, handle: &Handle<Image>) -> bool {
+        self.usage_updates.remove(handle)
+    }
+
+    /// Sets the image that levels are spawned with in place of a reloaded tileset image.
+    pub(crate) fn replace(&mut self, handle: &Handle<Image>, replacement: Handle<Image>) {
+        self.reloaded.insert(handle.clone_weak(), replacement);
+    }
+
+    /// The image that levels should be spawned with for the given tileset image.
+    pub(crate) fn get(&self, handle: &Handle<Image>) -> Handle<Image> {
+        self.reloaded
+            .get(handle)
+            .cloned()
+            .unwrap_or_else(|| handle.clone())
+    }
+}
+
+/// Resource allocating the bevy_ecs_tilemap map ids of spawned levels.
+///
+/// Map ids are only 16 bits wide, so they can't be derived from level uids without collisions.
+/// Instead, each spawned level is given an id that no other spawned level is using, which is
+/// released when the level despawns.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct LevelMapIds {
+    in_use: HashSet<u16>,
+    next: u16,
+}
+
+impl LevelMapIds {
+    /// Returns a map id that isn't used by any other spawned level.
+    ///
+    /// Panics if every map id is in use.
+    pub(crate) fn allocate(&mut self) -> u16 {
+        assert!(
+            self.in_use.len() <= u16::MAX as usize,
+            "every bevy_ecs_tilemap map id is used by a spawned level"
+        );
+
+        while !self.in_use.insert(self.next) {
+            self.next = self.next.wrapping_add(1);
+        }
+
+        let id = self.next;
+        self.next = self.next.wrapping_add(1);
+        id
+    }
+
+    /// Makes the map id of a despawned level available again.
+    pub(crate) fn release(&mut self, id: u16) {
+        self.in_use.remove(&id);
+    }
+}
+
+/// Resource storing hashes of the contents of loaded LDtk projects.
+///
+/// Used to determine which levels need to be respawned when a project is hot-reloaded.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct LdtkProjectHashes {
+    projects: HashMap<Handle<LdtkAsset>, ProjectHashes>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+struct ProjectHashes {
+    /// Hash of everything in the project besides its levels, like definitions.
+    project: u64,
+    levels: HashMap<i32, u64>,
+}
+
+impl LdtkProjectHashes {
+    /// Stores the hashes of the given project, returning the uids of the levels that have changed
+    /// since it was last stored.
+    ///
+    /// If anything outside of the levels changed, or the project wasn't stored yet, every level
+    /// is considered changed.
+    pub(crate) fn update(
+        &mut self,
+        handle: &Handle<LdtkAsset>,
+        project: &LdtkJson,
+    ) -> HashSet<i32> {
+        let new_hashes = ProjectHashes {
+            project: hash_serialized(&LdtkJson {
+                levels: Vec::new(),
+                ..project.clone()
+            }),
+            levels: project
+                .levels
+                .iter()
+                .map(|level| (level.uid, hash_serialized(level)))
+                .collect(),
+        };
+
+        let changed_level_uids = match self.projects.get(handle) {
+            Some(old_hashes) if old_hashes.project == new_hashes.project => new_hashes
+                .levels
+                .iter()
+                .filter(|(uid, hash)| old_hashes.levels.get(uid) != Some(hash))
+                .map(|(uid, _)| *uid)
+                .collect(),
+            _ => new_hashes.levels.keys().copied().collect(),
+        };
+
+        self.projects.insert(handle.clone(), new_hashes);
+
+        changed_level_uids
+    }
+
+    pub(crate) fn remove(&mut self, handle: &Handle<LdtkAsset>) {
+        self.projects.remove(handle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_map_ids() {
+        let mut map_ids = LevelMapIds::default();
+
+        let ids: HashSet<u16> = (0..3).map(|_| map_ids.allocate()).collect();
+        assert_eq!(ids.len(), 3);
+
+        // Released ids are reused once the others are exhausted, never while still in use.
+        map_ids.release(1);
+        for _ in 3..=u16::MAX {
+            map_ids.allocate();
+        }
+        assert_eq!(map_ids.allocate(), 1);
+
+        map_ids.release(u16::MAX);
+        assert_eq!(map_ids.allocate(), u16::MAX);
+    }
+
+    #[test]
+    fn test_ldtk_project_hashes_are_stable_across_parses() {
+        // The project contains HashMaps, which serialize in a different order for each parse.
+        let parse = || -> LdtkJson {
+            serde_json::from_str(include_str!("../assets/WorldMap_Free_layout.ldtk")).unwrap()
+        };
+
+        let handle = Handle::<LdtkAsset>::default();
+        let mut project_hashes = LdtkProjectHashes::default();
+        project_hashes.update(&handle, &parse());
+
+        for _ in 0..20 {
+            assert_eq!(project_hashes.update(&handle, &parse()), HashSet::new());
+        }
+    }
+
+    #[test]
+    fn test_ldtk_project_hashes() {
+        let mut project: LdtkJson =
+            serde_json::from_str(include_str!("../assets/WorldMap_Free_layout.ldtk")).unwrap();
+        let all_uids: HashSet<i32> = project.levels.iter().map(|l| l.uid).collect();
+        assert!(all_uids.len() > 1);
+
+        let handle = Handle::<LdtkAsset>::default();
+        let mut project_hashes = LdtkProjectHashes::default();
+
+        assert_eq!(project_hashes.update(&handle, &project), all_uids);
+        assert_eq!(project_hashes.update(&handle, &project), HashSet::new());
+
+        project.levels[1].px_wid += 1;
+        assert_eq!(
+            project_hashes.update(&handle, &project),
+            HashSet::from([project.levels[1].uid])
+        );
+
+        project.default_grid_size += 1;
+        assert_eq!(project_hashes.update(&handle, &project), all_uids);
+
+        project_hashes.remove(&handle);
+        assert_eq!(project_hashes.update(&handle, &project), all_uids);
+    }
 }
@@ -2,22 +2,26 @@
 
 use crate::{
     app::{
-        LdtkEntity, LdtkEntityMap, LdtkIntCellMap, PhantomLdtkEntity, PhantomLdtkEntityTrait,
-        PhantomLdtkIntCell, PhantomLdtkIntCellTrait,
+        DefaultLdtkLayerSpawner, LayerSpawnContext, LdtkEntityMap, LdtkIntCellMap,
+        LdtkLayerSpawner, LdtkLayerSpawnerMap,
     },
     assets::{LdtkAsset, LdtkLevel, TilesetMap},
     components::*,
-    ldtk::{EntityDefinition, LayerDefinition, Level, TileInstance, TilesetDefinition, Type},
-    resources::{LdtkSettings, LevelEvent, LevelSelection},
-    tile_makers::*,
+    ldtk::{EntityDefinition, LayerDefinition, LdtkJson, Level, TilesetDefinition},
+    resources::{
+        LdtkLoadErrorEvent, LdtkLoadErrorQueue, LdtkProjectHashes, LdtkSettings, LdtkWorldEvent,
+        LevelEvent, LevelMapIds, LevelSelection, TilesetImages, WhiteImages,
+    },
     utils::*,
+    validation::validate_registrations,
 };
 
-use bevy::{prelude::*, render::render_resource::*};
+use bevy::{asset::LoadState, ecs::system::SystemParam, prelude::*, render::render_resource::*};
 use bevy_ecs_tilemap::prelude::*;
 use std::collections::{HashMap, HashSet};
 
-const CHUNK_SIZE: ChunkSize = ChunkSize(32, 32);
+#[allow(unused_imports)]
+use crate::resources::ExternalLevelLoading;
 
 pub fn choose_levels(
     level_selection: Option<Res<LevelSelection>>,
@@ -50,20 +54,19 @@ pub fn choose_levels(
 pub fn apply_level_set(
     mut commands: Commands,
     ldtk_world_query: Query<(Entity, &LevelSet, &Children, &Handle<LdtkAsset>), Changed<LevelSet>>,
-    ldtk_level_query: Query<&Handle<LdtkLevel>>,
+    ldtk_level_query: Query<(&LevelUid, Option<&Map>)>,
     ldtk_assets: Res<Assets<LdtkAsset>>,
-    level_assets: Res<Assets<LdtkLevel>>,
     ldtk_settings: Res<LdtkSettings>,
-    mut map_query: MapQuery,
+    mut level_despawner: LevelDespawner,
     mut level_events: EventWriter<LevelEvent>,
+    asset_server: Res<AssetServer>,
+    level_assets: Res<Assets<LdtkLevel>>,
 ) {
     for (world_entity, level_set, children, ldtk_asset_handle) in ldtk_world_query.iter() {
         let mut previous_level_map = HashMap::new();
         for child in children.iter() {
-            if let Ok(level_handle) = ldtk_level_query.get(*child) {
-                if let Some(ldtk_level) = level_assets.get(level_handle) {
-                    previous_level_map.insert(ldtk_level.level.uid, child);
-                }
+            if let Ok((level_uid, map)) = ldtk_level_query.get(*child) {
+                previous_level_map.insert(level_uid.0, (*child, map));
             }
         }
 
@@ -74,35 +77,49 @@ pub fn apply_level_set(
             if let Some(ldtk_asset) = ldtk_assets.get(ldtk_asset_handle) {
                 commands.entity(world_entity).with_children(|c| {
                     for uid in uids_to_spawn {
-                        level_events.send(LevelEvent::SpawnTriggered(*uid));
-                        pre_spawn_level(c, ldtk_asset, *uid, &ldtk_settings);
+                        if pre_spawn_level(
+                            c,
+                            ldtk_asset,
+                            *uid,
+                            &ldtk_settings,
+                            &asset_server,
+                            &level_assets,
+                        ) {
+                            level_events.send(LevelEvent::SpawnTriggered(*uid));
+                        }
                     }
                 });
             }
         }
 
         for uid in previous_uids.difference(&level_set.uids) {
-            map_query.despawn(&mut commands, *uid as u16);
+            let (level_entity, map) = previous_level_map[uid];
+            level_despawner.despawn_level(&mut commands, level_entity, map);
             level_events.send(LevelEvent::Despawned(*uid));
         }
     }
 }
 
 /// Detects [LdtkAsset] events and spawns levels as children of the [LdtkWorldBundle].
+///
+/// When a project is modified, only the levels whose content changed are respawned.
 #[allow(clippy::too_many_arguments)]
 pub fn process_ldtk_world(
     mut commands: Commands,
     mut ldtk_events: EventReader<AssetEvent<LdtkAsset>>,
     mut level_events: EventWriter<LevelEvent>,
     new_ldtks: Query<&Handle<LdtkAsset>, Added<Handle<LdtkAsset>>>,
-    mut ldtk_level_query: Query<&mut Map, With<Handle<LdtkLevel>>>,
+    ldtk_level_query: Query<(&LevelUid, Option<&Map>)>,
     mut ldtk_world_query: Query<(Entity, &Handle<LdtkAsset>, &mut LevelSet, Option<&Children>)>,
     level_selection: Option<Res<LevelSelection>>,
     ldtk_assets: Res<Assets<LdtkAsset>>,
     ldtk_settings: Res<LdtkSettings>,
     mut clear_color: Option<ResMut<ClearColor>>,
-    layer_query: Query<&Layer>,
-    chunk_query: Query<&Chunk>,
+    mut level_despawner: LevelDespawner,
+    worldly_query: Query<&Worldly>,
+    mut project_hashes: ResMut<LdtkProjectHashes>,
+    asset_server: Res<AssetServer>,
+    level_assets: Res<Assets<LdtkLevel>>,
 ) {
     // This function uses code from the bevy_ecs_tilemap ldtk example
     // https://github.com/StarArawn/bevy_ecs_tilemap/blob/main/examples/ldtk/ldtk.rs
@@ -119,6 +136,7 @@ pub fn process_ldtk_world(
             }
             AssetEvent::Removed { handle } => {
                 info!("LDtk asset removal detected.");
+                project_hashes.remove(handle);
                 // if mesh was modified and removed in the same update, ignore the modification
                 // events are ordered so future modification events are ok
                 changed_ldtks = changed_ldtks
@@ -134,23 +152,63 @@ pub fn process_ldtk_world(
     }
 
     for changed_ldtk in changed_ldtks {
+        // Only levels whose content has changed since the last time this project was loaded are
+        // respawned, so unaffected levels keep their state through hot reloads.
+        let changed_level_uids = match ldtk_assets.get(&changed_ldtk) {
+            Some(ldtk_asset) => project_hashes.update(&changed_ldtk, &ldtk_asset.project),
+            None => HashSet::new(),
+        };
+
         for (ldtk_entity, ldtk_handle, mut level_set, children) in ldtk_world_query
             .iter_mut()
             .filter(|(_, l, _, _)| **l == changed_ldtk)
         {
+            let ldtk_asset = ldtk_assets.get(ldtk_handle);
+
+            // Levels newly added to a LevelSet that changed elsewhere are spawned by
+            // apply_level_set, like levels inserted at runtime along with their uids.
+            // That's only the case for worlds that already have children though, since
+            // apply_level_set requires them.
+            let level_set_changed = level_set.is_changed() && children.is_some();
+
+            let mut kept_level_uids = HashSet::new();
+            let mut reloaded_level_uids = HashSet::new();
+
             if let Some(children) = children {
                 for child in children.iter() {
-                    if let Ok(mut map) = ldtk_level_query.get_mut(*child) {
-                        clear_map(&mut commands, &mut map, &layer_query, &chunk_query);
-                        map.despawn(&mut commands);
-                        level_events.send(LevelEvent::Despawned(map.id as i32));
+                    if let Ok((level_uid, map)) = ldtk_level_query.get(*child) {
+                        let still_exists = ldtk_asset
+                            .map(|l| l.has_level(level_uid.0))
+                            .unwrap_or(false);
+
+                        if still_exists && !changed_level_uids.contains(&level_uid.0) {
+                            kept_level_uids.insert(level_uid.0);
+                        } else {
+                            level_despawner.despawn_level(&mut commands, *child, map);
+                            level_events.send(LevelEvent::Despawned(level_uid.0));
+
+                            if still_exists {
+                                reloaded_level_uids.insert(level_uid.0);
+                            }
+                        }
+                    } else if let Ok(worldly) = worldly_query.get(*child) {
+                        // Worldly entities are only respawned along with the level they came
+                        // from, so those of unchanged levels are kept regardless.
+                        let level_changed = changed_level_uids.contains(&worldly.spawn_level)
+                            || !ldtk_asset
+                                .map(|l| l.has_level(worldly.spawn_level))
+                                .unwrap_or(false);
+
+                        if ldtk_settings.despawn_worldly_on_reload && level_changed {
+                            commands.entity(*child).despawn_recursive();
+                        }
                     } else {
                         commands.entity(*child).despawn_recursive();
                     }
                 }
             }
 
-            if let Some(ldtk_asset) = ldtk_assets.get(ldtk_handle) {
+            if let Some(ldtk_asset) = ldtk_asset {
                 if ldtk_settings.set_clear_color {
                     if let Some(clear_color) = &mut clear_color {
                         clear_color.0 = ldtk_asset.project.bg_color;
@@ -174,9 +232,143 @@ pub fn process_ldtk_world(
                 }
 
                 commands.entity(ldtk_entity).with_children(|c| {
-                    for level_uid in &level_set.uids {
-                        level_events.send(LevelEvent::SpawnTriggered(*level_uid));
-                        pre_spawn_level(c, ldtk_asset, *level_uid, &ldtk_settings)
+                    for level_uid in level_set.uids.difference(&kept_level_uids) {
+                        if reloaded_level_uids.contains(level_uid) {
+                            level_events.send(LevelEvent::Reloaded(*level_uid));
+                        } else if level_set_changed {
+                            continue;
+                        }
+
+                        if pre_spawn_level(
+                            c,
+                            ldtk_asset,
+                            *level_uid,
+                            &ldtk_settings,
+                            &asset_server,
+                            &level_assets,
+                        ) {
+                            level_events.send(LevelEvent::SpawnTriggered(*level_uid));
+                        }
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Detects [LdtkLevel] and tileset [Image] modifications and respawns only the affected levels.
+///
+/// [LdtkLevel] modifications are only considered for projects with external levels.
+/// The levels of standalone projects are reloaded along with the project in
+/// [process_ldtk_world].
+///
+/// Modifications of tileset images caused by [set_ldtk_texture_filters_to_nearest] are ignored.
+/// Without the "atlas" feature, `bevy_ecs_tilemap` caches tileset textures for the lifetime of
+/// the app, so reloaded tileset images are copied to a new image for the respawned levels to use.
+/// See [TilesetImages] for more details.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn process_ldtk_level_reloads(
+    mut commands: Commands,
+    mut level_asset_events: EventReader<AssetEvent<LdtkLevel>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut level_events: EventWriter<LevelEvent>,
+    ldtk_world_query: Query<(Entity, &Handle<LdtkAsset>, &Children)>,
+    ldtk_level_query: Query<(&LevelUid, &Handle<LdtkLevel>, Option<&Map>)>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    ldtk_settings: Res<LdtkSettings>,
+    mut level_despawner: LevelDespawner,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut tileset_images: ResMut<TilesetImages>,
+) {
+    let modified_levels: HashSet<Handle<LdtkLevel>> = level_asset_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { handle } => Some(handle.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let modified_images: HashSet<Handle<Image>> = image_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { handle } if !tileset_images.take_usage_update(handle) => {
+                Some(handle.clone())
+            }
+            _ => None,
+        })
+        .collect();
+
+    if cfg!(not(feature = "atlas")) {
+        for image_handle in modified_images.iter().filter(|image_handle| {
+            ldtk_assets
+                .iter()
+                .any(|(_, ldtk_asset)| ldtk_asset.tileset_map.values().any(|h| h == *image_handle))
+        }) {
+            if let Some(image) = images.get(image_handle) {
+                let mut image = image.clone();
+                image.texture_descriptor.usage = tileset_texture_usages();
+
+                let replacement = images.add(image);
+                tileset_images.replace(image_handle, replacement);
+            }
+        }
+    }
+
+    if modified_levels.is_empty() && modified_images.is_empty() {
+        return;
+    }
+
+    for (ldtk_entity, ldtk_handle, children) in ldtk_world_query.iter() {
+        if let Some(ldtk_asset) = ldtk_assets.get(ldtk_handle) {
+            let modified_tileset_uids: Vec<i32> = ldtk_asset
+                .tileset_map
+                .iter()
+                .filter(|(_, image_handle)| modified_images.contains(image_handle))
+                .map(|(uid, _)| *uid)
+                .collect();
+
+            let mut reloaded_level_uids = Vec::new();
+
+            for child in children.iter() {
+                if let Ok((level_uid, level_handle, map)) = ldtk_level_query.get(*child) {
+                    let level_modified = ldtk_asset.project.external_levels
+                        && modified_levels.contains(level_handle);
+
+                    let tileset_modified = level_assets
+                        .get(level_handle)
+                        .map(|level| {
+                            modified_tileset_uids
+                                .iter()
+                                .any(|uid| level.uses_tileset(*uid))
+                        })
+                        .unwrap_or(false);
+
+                    if level_modified || tileset_modified {
+                        info!("LDtk level modification detected.");
+                        level_despawner.despawn_level(&mut commands, *child, map);
+                        level_events.send(LevelEvent::Despawned(level_uid.0));
+
+                        reloaded_level_uids.push(level_uid.0);
+                    }
+                }
+            }
+
+            if !reloaded_level_uids.is_empty() {
+                commands.entity(ldtk_entity).with_children(|c| {
+                    for level_uid in reloaded_level_uids {
+                        level_events.send(LevelEvent::Reloaded(level_uid));
+                        if pre_spawn_level(
+                            c,
+                            ldtk_asset,
+                            level_uid,
+                            &ldtk_settings,
+                            &asset_server,
+                            &level_assets,
+                        ) {
+                            level_events.send(LevelEvent::SpawnTriggered(level_uid));
+                        }
                     }
                 });
             }
@@ -184,13 +376,18 @@ pub fn process_ldtk_world(
     }
 }
 
+/// Spawns the level entity, which is filled out by [process_ldtk_levels] once its asset loads.
+///
+/// Returns true if the level's asset is already loaded, meaning the level will spawn this update.
 fn pre_spawn_level(
     child_builder: &mut ChildBuilder,
     ldtk_asset: &LdtkAsset,
     level_uid: i32,
     ldtk_settings: &LdtkSettings,
-) {
-    if let Some(level_handle) = ldtk_asset.level_map.get(&level_uid) {
+    asset_server: &AssetServer,
+    level_assets: &Assets<LdtkLevel>,
+) -> bool {
+    if let Some(level_handle) = ldtk_asset.get_level_handle(level_uid, asset_server) {
         let mut translation = Vec3::ZERO;
 
         if ldtk_settings.use_level_world_translations {
@@ -209,51 +406,102 @@ fn pre_spawn_level(
             }
         }
 
+        let loaded = level_assets.get(&level_handle).is_some();
+
         child_builder
             .spawn()
-            .insert(level_handle.clone())
+            .insert(level_handle)
+            .insert(LevelUid(level_uid))
             .insert_bundle((
                 Transform::from_translation(translation),
                 GlobalTransform::default(),
             ));
+
+        loaded
+    } else {
+        false
     }
 }
 
-fn clear_map(
-    commands: &mut Commands,
-    map: &mut Map,
-    layer_query: &Query<&Layer>,
-    chunk_query: &Query<&Chunk>,
-) {
-    for (layer_id, layer_entity) in map.get_layers() {
-        if let Ok(layer) = layer_query.get(layer_entity) {
-            for x in 0..layer.get_layer_size_in_tiles().0 {
-                for y in 0..layer.get_layer_size_in_tiles().1 {
-                    let tile_pos = TilePos(x, y);
-                    let chunk_pos = ChunkPos(
-                        tile_pos.0 / layer.settings.chunk_size.0,
-                        tile_pos.1 / layer.settings.chunk_size.1,
-                    );
-                    if let Some(chunk_entity) = layer.get_chunk(chunk_pos) {
-                        if let Ok(chunk) = chunk_query.get(chunk_entity) {
-                            let chunk_tile_pos = chunk.to_chunk_pos(tile_pos);
-                            if let Some(tile) = chunk.get_tile_entity(chunk_tile_pos) {
-                                commands.entity(tile).despawn_recursive();
+/// [SystemParam] for despawning levels, along with their tilemap layers, tiles, and LDtk entities.
+///
+/// [Worldly] entities are not despawned once they have been adopted by the world.
+/// This is the only way levels are despawned by the plugin.
+#[derive(SystemParam)]
+pub struct LevelDespawner<'w, 's> {
+    layer_query: Query<'w, 's, &'static Layer>,
+    chunk_query: Query<'w, 's, &'static Chunk>,
+    map_ids: ResMut<'w, LevelMapIds>,
+}
+
+impl<'w, 's> LevelDespawner<'w, 's> {
+    /// Despawns a level entity, releasing its map id if it has finished spawning.
+    pub(crate) fn despawn_level(
+        &mut self,
+        commands: &mut Commands,
+        level_entity: Entity,
+        map: Option<&Map>,
+    ) {
+        // Tiles aren't children of their layers, unlike chunks, so they need to be despawned
+        // separately.
+        if let Some(map) = map {
+            for (_, layer_entity) in map.get_layers() {
+                if let Ok(layer) = self.layer_query.get(layer_entity) {
+                    for x in 0..layer.get_layer_size_in_tiles().0 {
+                        for y in 0..layer.get_layer_size_in_tiles().1 {
+                            let tile_pos = TilePos(x, y);
+                            let chunk_pos = ChunkPos(
+                                tile_pos.0 / layer.settings.chunk_size.0,
+                                tile_pos.1 / layer.settings.chunk_size.1,
+                            );
+                            if let Some(chunk_entity) = layer.get_chunk(chunk_pos) {
+                                if let Ok(chunk) = self.chunk_query.get(chunk_entity) {
+                                    let chunk_tile_pos = chunk.to_chunk_pos(tile_pos);
+                                    if let Some(tile) = chunk.get_tile_entity(chunk_tile_pos) {
+                                        commands.entity(tile).despawn_recursive();
+                                    }
+                                }
                             }
                         }
-
-                        commands.entity(chunk_entity).despawn_recursive();
                     }
                 }
             }
 
-            map.remove_layer(commands, layer_id);
+            self.map_ids.release(map.id);
+        }
+
+        commands.entity(level_entity).despawn_recursive();
+    }
+}
+
+/// Fires [LevelEvent::SpawnTriggered] events for levels whose assets finished loading after their
+/// level entities were spawned, as with [ExternalLevelLoading::Lazy].
+pub fn trigger_loaded_level_spawns(
+    mut level_asset_events: EventReader<AssetEvent<LdtkLevel>>,
+    level_query: Query<(&LevelUid, &Handle<LdtkLevel>), Without<Map>>,
+    mut level_events: EventWriter<LevelEvent>,
+) {
+    let loaded_levels: HashSet<Handle<LdtkLevel>> = level_asset_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } => Some(handle.clone()),
+            _ => None,
+        })
+        .collect();
+
+    if loaded_levels.is_empty() {
+        return;
+    }
+
+    for (level_uid, level_handle) in level_query.iter() {
+        if loaded_levels.contains(level_handle) {
+            level_events.send(LevelEvent::SpawnTriggered(level_uid.0));
         }
     }
 }
 
-/// Performs all the spawning of levels, layers, chunks, bundles, entities, tiles, etc. when an
-/// LdtkLevelBundle is added.
+/// Performs all the spawning of levels, layers, chunks, bundles, entities, tiles, etc. for level
+/// entities that haven't been spawned yet, once their level assets have loaded.
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn process_ldtk_levels(
     mut commands: Commands,
@@ -265,9 +513,16 @@ pub fn process_ldtk_levels(
     level_assets: Res<Assets<LdtkLevel>>,
     ldtk_entity_map: NonSend<LdtkEntityMap>,
     ldtk_int_cell_map: NonSend<LdtkIntCellMap>,
+    ldtk_layer_spawner_map: NonSend<LdtkLayerSpawnerMap>,
     ldtk_query: Query<&Handle<LdtkAsset>>,
-    level_query: Query<(Entity, &Handle<LdtkLevel>, &Parent), Added<Handle<LdtkLevel>>>,
+    level_query: Query<(Entity, &Handle<LdtkLevel>, &Parent), Without<Map>>,
     worldly_query: Query<&Worldly>,
+    ldtk_settings: Res<LdtkSettings>,
+    (mut white_images, mut map_ids, tileset_images): (
+        ResMut<WhiteImages>,
+        ResMut<LevelMapIds>,
+        Res<TilesetImages>,
+    ),
     mut level_events: EventWriter<LevelEvent>,
 ) {
     // This function uses code from the bevy_ecs_tilemap ldtk example
@@ -290,6 +545,12 @@ pub fn process_ldtk_levels(
                 let layer_definition_map =
                     create_layer_definition_map(&ldtk_asset.project.defs.layers);
 
+                let tileset_map: TilesetMap = ldtk_asset
+                    .tileset_map
+                    .iter()
+                    .map(|(uid, image_handle)| (*uid, tileset_images.get(image_handle)))
+                    .collect();
+
                 let worldly_set = worldly_query.iter().cloned().collect();
 
                 if let Some(level) = level_assets.get(level_handle) {
@@ -302,12 +563,16 @@ pub fn process_ldtk_levels(
                         &mut meshes,
                         &ldtk_entity_map,
                         &ldtk_int_cell_map,
+                        &ldtk_layer_spawner_map,
                         &entity_definition_map,
                         &layer_definition_map,
-                        &ldtk_asset.tileset_map,
+                        &tileset_map,
                         &tileset_definition_map,
                         worldly_set,
                         ldtk_entity,
+                        &ldtk_settings,
+                        &mut white_images,
+                        map_ids.allocate(),
                     );
                     level_events.send(LevelEvent::Spawned(level.level.uid));
                 }
@@ -326,40 +591,33 @@ fn spawn_level(
     meshes: &mut ResMut<Assets<Mesh>>,
     ldtk_entity_map: &LdtkEntityMap,
     ldtk_int_cell_map: &LdtkIntCellMap,
+    ldtk_layer_spawner_map: &LdtkLayerSpawnerMap,
     entity_definition_map: &HashMap<i32, &EntityDefinition>,
     layer_definition_map: &HashMap<i32, &LayerDefinition>,
     tileset_map: &TilesetMap,
     tileset_definition_map: &HashMap<i32, &TilesetDefinition>,
     worldly_set: HashSet<Worldly>,
     ldtk_entity: Entity,
+    ldtk_settings: &LdtkSettings,
+    white_images: &mut WhiteImages,
+    map_id: u16,
 ) {
-    let mut map = Map::new(level.uid as u16, ldtk_entity);
+    let mut map = Map::new(map_id, ldtk_entity);
 
     if let Some(layer_instances) = &level.layer_instances {
-        let mut layer_id = 0;
-
-        // creating an image to use for the background color, and for intgrid colors
-        let mut white_image = Image::new_fill(
-            Extent3d {
-                width: level.px_wid as u32,
-                height: level.px_hei as u32,
-                depth_or_array_layers: 1,
-            },
-            TextureDimension::D2,
-            &[255, 255, 255, 255],
-            TextureFormat::Rgba8UnormSrgb,
-        );
-        white_image.texture_descriptor.usage =
-            TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::COPY_DST;
-
-        let white_image_handle = images.add(white_image);
+        // Each bevy_ecs_tilemap layer needs its own id, which also determines its z ordering.
+        // The z of each LDtk layer is at least `layer_z_step` above the previous LDtk layer, and
+        // above every tilemap layer spawned for it.
+        let mut layer_id = ldtk_settings.layer_z_base;
+        let mut layer_z = layer_id;
 
         {
+            // The background is a single white 1x1 tile, colored and scaled to fill the level.
             let settings = LayerSettings::new(
                 MapSize(1, 1),
                 ChunkSize(1, 1),
-                TileSize(level.px_wid as f32, level.px_hei as f32),
-                TextureSize(level.px_wid as f32, level.px_hei as f32),
+                TileSize(1., 1.),
+                TextureSize(1., 1.),
             );
 
             let (mut layer_builder, layer_entity) =
@@ -379,330 +637,103 @@ fn spawn_level(
                 Err(_) => warn!("Encountered error when setting background tile"),
             }
 
-            let layer_bundle = layer_builder.build(commands, meshes, white_image_handle.clone());
+            let white_image_handle = white_images.get_or_add(1, images);
+            let layer_bundle = layer_builder.build(commands, meshes, white_image_handle);
             commands.entity(layer_entity).insert_bundle(layer_bundle);
+            commands
+                .entity(layer_entity)
+                .insert(Transform::from_scale(Vec3::new(
+                    level.px_wid as f32,
+                    level.px_hei as f32,
+                    1.,
+                )));
             map.add_layer(commands, layer_id, layer_entity);
             layer_id += 1;
         }
 
-        for layer_instance in layer_instances.iter().rev() {
-            match layer_instance.layer_instance_type {
-                Type::Entities => {
-                    commands.entity(ldtk_entity).with_children(|commands| {
-                        for entity_instance in &layer_instance.entity_instances {
-                            let transform = calculate_transform_from_entity_instance(
-                                entity_instance,
-                                entity_definition_map,
-                                level.px_hei,
-                                layer_id as f32,
-                            );
-                            // Note: entities do not seem to be affected visually by layer offsets in
-                            // the editor, so no layer offset is added to the transform here.
-
-                            let mut entity_commands = commands.spawn();
-
-                            let (tileset, tileset_definition) = match &entity_instance.tile {
-                                Some(t) => (
-                                    tileset_map.get(&t.tileset_uid),
-                                    tileset_definition_map.get(&t.tileset_uid).copied(),
-                                ),
-                                None => (None, None),
-                            };
-
-                            let predicted_worldly = Worldly::bundle_entity(
-                                entity_instance,
-                                layer_instance,
-                                tileset,
-                                tileset_definition,
-                                asset_server,
-                                texture_atlases,
-                            );
-
-                            if !worldly_set.contains(&predicted_worldly) {
-                                let default_ldtk_entity: Box<dyn PhantomLdtkEntityTrait> =
-                                    Box::new(PhantomLdtkEntity::<EntityInstanceBundle>::new());
-
-                                ldtk_map_get_or_default(
-                                    layer_instance.identifier.clone(),
-                                    entity_instance.identifier.clone(),
-                                    &default_ldtk_entity,
-                                    ldtk_entity_map,
-                                )
-                                .evaluate(
-                                    &mut entity_commands,
-                                    entity_instance,
-                                    layer_instance,
-                                    tileset,
-                                    tileset_definition,
-                                    asset_server,
-                                    texture_atlases,
-                                );
-
-                                entity_commands
-                                    .insert(transform)
-                                    .insert(GlobalTransform::default());
-                            }
-                        }
-                    });
-                }
-                _ => {
-                    // The remaining layers have a lot of shared code.
-                    // This is because:
-                    // 1. There is virtually no difference between AutoTile and Tile layers
-                    // 2. IntGrid layers can sometimes have AutoTile functionality
-
-                    let map_size = MapSize(
-                        (layer_instance.c_wid as f32 / CHUNK_SIZE.0 as f32).ceil() as u32,
-                        (layer_instance.c_hei as f32 / CHUNK_SIZE.1 as f32).ceil() as u32,
-                    );
+        let default_spawner: Box<dyn LdtkLayerSpawner> = Box::new(DefaultLdtkLayerSpawner);
+
+        let mut context = LayerSpawnContext {
+            asset_server,
+            images,
+            texture_atlases,
+            ldtk_entity_map,
+            ldtk_int_cell_map,
+            entity_definition_map,
+            layer_definition_map,
+            tileset_map,
+            tileset_definition_map,
+            worldly_set: &worldly_set,
+            ldtk_settings,
+            level,
+            level_entity: ldtk_entity,
+            map: &mut map,
+            white_images,
+            layer_z,
+            next_layer_id: layer_id,
+        };
 
-                    let tileset_definition = layer_instance
-                        .tileset_def_uid
-                        .map(|u| tileset_definition_map.get(&u).unwrap());
-
-                    let tile_size = match tileset_definition {
-                        Some(tileset_definition) => TileSize(
-                            tileset_definition.tile_grid_size as f32,
-                            tileset_definition.tile_grid_size as f32,
-                        ),
-                        None => TileSize(
-                            layer_instance.grid_size as f32,
-                            layer_instance.grid_size as f32,
-                        ),
-                    };
-
-                    let texture_size = match tileset_definition {
-                        Some(tileset_definition) => TextureSize(
-                            tileset_definition.px_wid as f32,
-                            tileset_definition.px_hei as f32,
-                        ),
-                        None => TextureSize(
-                            layer_instance.grid_size as f32,
-                            layer_instance.grid_size as f32,
-                        ),
-                    };
-
-                    let mut settings =
-                        LayerSettings::new(map_size, CHUNK_SIZE, tile_size, texture_size);
-
-                    if let Some(tileset_definition) = tileset_definition {
-                        settings.grid_size = Vec2::splat(layer_instance.grid_size as f32);
-                        if tileset_definition.spacing != 0 {
-                            #[cfg(not(feature = "atlas"))]
-                            {
-                                warn!(
-                                    "Tile spacing on Tile and AutoTile layers requires the \"atlas\" feature"
-                                );
-                            }
-
-                            #[cfg(feature = "atlas")]
-                            {
-                                settings.tile_spacing =
-                                    Vec2::splat(tileset_definition.spacing as f32);
-                            }
-                        }
-                    }
-
-                    // The change to the settings.grid_size above is supposed to help handle cases
-                    // where the tileset's tile size and the layer's tile size are different.
-                    // However, changing the grid_size doesn't have any affect with the current
-                    // bevy_ecs_tilemap, so the workaround is to scale up the entire layer.
-                    let layer_scale = (settings.grid_size
-                        / Vec2::new(settings.tile_size.0 as f32, settings.tile_size.1 as f32))
-                    .extend(1.);
-
-                    let image_handle = match tileset_definition {
-                        Some(tileset_definition) => {
-                            tileset_map.get(&tileset_definition.uid).unwrap().clone()
-                        }
-                        None => white_image_handle.clone(),
-                    };
-
-                    let mut grid_tiles = layer_instance.grid_tiles.clone();
-                    grid_tiles.extend(layer_instance.auto_layer_tiles.clone());
-
-                    for (i, grid_tiles) in layer_grid_tiles(grid_tiles).into_iter().enumerate() {
-                        let layer_entity = if layer_instance.layer_instance_type == Type::IntGrid {
-                            // The current spawning of IntGrid layers doesn't allow using
-                            // LayerBuilder::new_batch().
-                            // So, the actual LayerBuilder usage diverges greatly here
-
-                            let (mut layer_builder, layer_entity) =
-                                LayerBuilder::<TileGridBundle>::new(
-                                    commands,
-                                    settings,
-                                    map.id,
-                                    layer_id as u16,
-                                );
-
-                            match tileset_definition {
-                                Some(_) => {
-                                    set_all_tiles_with_func(
-                                        &mut layer_builder,
-                                        tile_pos_to_tile_bundle_maker(
-                                            tile_pos_to_int_grid_with_grid_tiles_tile_maker(
-                                                &grid_tiles,
-                                                &layer_instance.int_grid_csv,
-                                                layer_instance.c_wid,
-                                                layer_instance.c_hei,
-                                                layer_instance.grid_size,
-                                            ),
-                                        ),
-                                    );
-                                }
-                                None => {
-                                    let int_grid_value_defs = &layer_definition_map
-                                        .get(&layer_instance.layer_def_uid)
-                                        .expect("Encountered layer without definition")
-                                        .int_grid_values;
-
-                                    set_all_tiles_with_func(
-                                        &mut layer_builder,
-                                        tile_pos_to_tile_bundle_maker(
-                                            tile_pos_to_int_grid_colored_tile_maker(
-                                                &layer_instance.int_grid_csv,
-                                                int_grid_value_defs,
-                                                layer_instance.c_wid,
-                                                layer_instance.c_hei,
-                                            ),
-                                        ),
-                                    );
-                                }
-                            }
-
-                            if i == 0 {
-                                for (i, value) in layer_instance
-                                    .int_grid_csv
-                                    .iter()
-                                    .enumerate()
-                                    .filter(|(_, v)| **v != 0)
-                                {
-                                    let tile_pos = int_grid_index_to_tile_pos(
-                                i,
-                                layer_instance.c_wid as u32,
-                                layer_instance.c_hei as u32,
-                            ).expect("int_grid_csv indices should be within the bounds of 0..(layer_widthd * layer_height)");
-
-                                    let tile_entity =
-                                        layer_builder.get_tile_entity(commands, tile_pos).unwrap();
-
-                                    let mut translation = tile_pos_to_translation_centered(
-                                        tile_pos,
-                                        IVec2::splat(layer_instance.grid_size),
-                                    )
-                                    .extend(layer_id as f32);
-
-                                    translation /= layer_scale;
-
-                                    let mut entity_commands = commands.entity(tile_entity);
-
-                                    let default_ldtk_int_cell: Box<dyn PhantomLdtkIntCellTrait> =
-                                        Box::new(PhantomLdtkIntCell::<IntGridCellBundle>::new());
-
-                                    ldtk_map_get_or_default(
-                                        layer_instance.identifier.clone(),
-                                        *value,
-                                        &default_ldtk_int_cell,
-                                        ldtk_int_cell_map,
-                                    )
-                                    .evaluate(
-                                        &mut entity_commands,
-                                        IntGridCell { value: *value },
-                                        layer_instance,
-                                    );
-
-                                    entity_commands
-                                        .insert(Transform::from_translation(translation))
-                                        .insert(GlobalTransform::default())
-                                        .insert(Parent(layer_entity));
-                                }
-                            }
-
-                            let layer_bundle =
-                                layer_builder.build(commands, meshes, image_handle.clone());
-
-                            commands.entity(layer_entity).insert_bundle(layer_bundle);
-
-                            layer_entity
-                        } else {
-                            let tile_maker = tile_pos_to_tile_maker(
-                                &grid_tiles,
-                                layer_instance.c_hei,
-                                layer_instance.grid_size,
-                            );
-
-                            LayerBuilder::<TileGridBundle>::new_batch(
-                                commands,
-                                settings,
-                                meshes,
-                                image_handle.clone(),
-                                map.id,
-                                layer_id as u16,
-                                tile_pos_to_tile_bundle_maker(tile_maker),
-                            )
-                        };
-
-                        let layer_offset = Vec3::new(
-                            layer_instance.px_total_offset_x as f32,
-                            -layer_instance.px_total_offset_y as f32,
-                            0.,
-                        );
-
-                        commands.entity(layer_entity).insert(
-                            Transform::from_translation(layer_offset).with_scale(layer_scale),
-                        );
-
-                        map.add_layer(commands, layer_id as u16, layer_entity);
-                        layer_id += 1;
-                    }
-                }
-            }
+        for layer_instance in layer_instances.iter().rev() {
+            layer_z = layer_z
+                .saturating_add(ldtk_settings.layer_z_step)
+                .max(layer_id);
+
+            context.layer_z = layer_z;
+            context.next_layer_id = layer_z;
+
+            ldtk_map_get_or_default(
+                layer_instance.layer_instance_type.clone(),
+                layer_instance.identifier.clone(),
+                &default_spawner,
+                ldtk_layer_spawner_map,
+            )
+            .spawn_layer(commands, meshes, &mut context, layer_instance);
+
+            layer_id = context.next_layer_id;
         }
     }
     commands.entity(ldtk_entity).insert(map);
 }
 
-fn layer_grid_tiles(grid_tiles: Vec<TileInstance>) -> Vec<Vec<TileInstance>> {
-    let mut layer = Vec::new();
-    let mut overflow = Vec::new();
-    for tile in grid_tiles {
-        if layer.iter().any(|t: &TileInstance| t.px == tile.px) {
-            overflow.push(tile);
-        } else {
-            layer.push(tile);
-        }
-    }
-
-    let mut layered_grid_tiles = vec![layer];
-    if !overflow.is_empty() {
-        layered_grid_tiles.extend(layer_grid_tiles(overflow));
-    }
-
-    layered_grid_tiles
-}
-
 pub fn worldly_adoption(
     mut worldly_query: Query<(&mut Transform, &mut Parent), Added<Worldly>>,
     transform_query: Query<(&Transform, &Parent), Without<Worldly>>,
+    world_query: Query<(), With<Handle<LdtkAsset>>>,
 ) {
     for (mut transform, mut parent) in worldly_query.iter_mut() {
-        if let Ok((level_transform, level_parent)) = transform_query.get(parent.0) {
-            *transform = level_transform.mul_transform(*transform);
-            parent.0 = level_parent.0
+        // Worldly entities are born in an entity layer, which is a child of the level.
+        // Climb the hierarchy until the world is reached, accumulating transforms along the way.
+        while world_query.get(parent.0).is_err() {
+            if let Ok((ancestor_transform, ancestor_parent)) = transform_query.get(parent.0) {
+                *transform = ancestor_transform.mul_transform(*transform);
+                parent.0 = ancestor_parent.0;
+            } else {
+                break;
+            }
         }
     }
 }
 
+fn tileset_texture_usages() -> TextureUsages {
+    TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::COPY_DST
+}
+
 pub fn set_ldtk_texture_filters_to_nearest(
     mut texture_events: EventReader<AssetEvent<Image>>,
     mut textures: ResMut<Assets<Image>>,
     ldtk_assets: Res<Assets<LdtkAsset>>,
+    mut tileset_images: ResMut<TilesetImages>,
 ) {
     // Based on
     // https://github.com/StarArawn/bevy_ecs_tilemap/blob/main/examples/helpers/texture.rs,
     // except it only applies to the ldtk tilesets.
+    // Reloaded tilesets are replaced by a fresh image, so modifications are handled too.
+    // The usage is only written when it differs, since get_mut() sends another Modified event.
+    // The resulting Modified event is recorded in TilesetImages, so it isn't mistaken for a hot
+    // reload.
+    let usage = tileset_texture_usages();
     for event in texture_events.iter() {
-        if let AssetEvent::Created { handle } = event {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
             let mut set_texture_filters_to_nearest = false;
 
             for (_, ldtk_asset) in ldtk_assets.iter() {
@@ -712,14 +743,158 @@ pub fn set_ldtk_texture_filters_to_nearest(
                 }
             }
 
-            if set_texture_filters_to_nearest {
+            let needs_usage = textures
+                .get(handle)
+                .map(|texture| texture.texture_descriptor.usage != usage)
+                .unwrap_or(false);
+
+            if set_texture_filters_to_nearest && needs_usage {
                 if let Some(mut texture) = textures.get_mut(handle) {
-                    texture.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
-                        | TextureUsages::COPY_SRC
-                        | TextureUsages::COPY_DST;
+                    texture.texture_descriptor.usage = usage;
+                    tileset_images.add_usage_update(handle);
+                }
+            }
+        }
+    }
+}
+
+/// Updates the [LdtkLoadProgress] of each world, firing [LdtkWorldEvent::Ready] events for worlds
+/// that have just become ready.
+#[allow(clippy::type_complexity)]
+pub fn update_load_progress(
+    mut ldtk_world_query: Query<(
+        Entity,
+        &Handle<LdtkAsset>,
+        &LevelSet,
+        Option<&Children>,
+        &mut LdtkLoadProgress,
+    )>,
+    ldtk_level_query: Query<(&LevelUid, &Handle<LdtkLevel>, Option<&Map>)>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    images: Res<Assets<Image>>,
+    mut world_events: EventWriter<LdtkWorldEvent>,
+) {
+    for (ldtk_entity, ldtk_handle, level_set, children, mut load_progress) in
+        ldtk_world_query.iter_mut()
+    {
+        let mut new_progress = LdtkLoadProgress {
+            levels_total: level_set.uids.len(),
+            ..Default::default()
+        };
+        let mut unknown_level_uids = Vec::new();
+
+        if let Some(ldtk_asset) = ldtk_assets.get(ldtk_handle) {
+            // Levels the project doesn't have never spawn, so they would block readiness.
+            unknown_level_uids = level_set
+                .uids
+                .iter()
+                .filter(|uid| !ldtk_asset.has_level(**uid))
+                .collect();
+
+            new_progress.project_loaded = true;
+            new_progress.levels_total -= unknown_level_uids.len();
+            new_progress.tilesets_total = ldtk_asset.tileset_map.len();
+            new_progress.tilesets_loaded = ldtk_asset
+                .tileset_map
+                .values()
+                .filter(|image_handle| images.get(*image_handle).is_some())
+                .count();
+        }
+
+        for child in children.iter().flat_map(|children| children.iter()) {
+            if let Ok((level_uid, level_handle, map)) = ldtk_level_query.get(*child) {
+                if level_set.uids.contains(&level_uid.0) {
+                    if level_assets.get(level_handle).is_some() {
+                        new_progress.levels_loaded += 1;
+                    }
+
+                    if map.is_some() {
+                        new_progress.levels_spawned += 1;
+                    }
                 }
             }
         }
+
+        if *load_progress != new_progress {
+            if !unknown_level_uids.is_empty() {
+                warn!(
+                    "LevelSet contains uids of levels that don't exist in the project: {:?}",
+                    unknown_level_uids
+                );
+            }
+
+            if new_progress.is_ready() && !load_progress.is_ready() {
+                world_events.send(LdtkWorldEvent::Ready(ldtk_entity));
+            }
+
+            *load_progress = new_progress;
+        }
+    }
+}
+
+/// Reports registered [LdtkEntity](crate::app::LdtkEntity)s and
+/// [LdtkIntCell](crate::app::LdtkIntCell)s that don't match anything in the loaded projects.
+///
+/// Registrations are checked against all projects at once, and only after every project of the
+/// spawned worlds has finished loading, since each registration only needs to match one of them.
+///
+/// Panics in debug builds if [LdtkSettings::strict_registrations] is enabled.
+#[allow(clippy::too_many_arguments)]
+pub fn check_registrations(
+    mut ldtk_events: EventReader<AssetEvent<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    asset_server: Res<AssetServer>,
+    ldtk_entity_map: NonSend<LdtkEntityMap>,
+    ldtk_int_cell_map: NonSend<LdtkIntCellMap>,
+    ldtk_settings: Res<LdtkSettings>,
+    mut pending: Local<bool>,
+) {
+    for event in ldtk_events.iter() {
+        if let AssetEvent::Created { .. } | AssetEvent::Modified { .. } = event {
+            *pending = true;
+        }
+    }
+
+    let all_loaded = ldtk_query.iter().all(|handle| {
+        ldtk_assets.get(handle).is_some()
+            || asset_server.get_load_state(handle) == LoadState::Failed
+    });
+
+    if !*pending || !all_loaded {
+        return;
+    }
+
+    *pending = false;
+
+    let projects: Vec<&LdtkJson> = ldtk_assets
+        .iter()
+        .map(|(_, ldtk_asset)| &ldtk_asset.project)
+        .collect();
+
+    let diagnostics = validate_registrations(&projects, &ldtk_entity_map, &ldtk_int_cell_map);
+
+    for diagnostic in &diagnostics {
+        warn!("{}", diagnostic);
+    }
+
+    if ldtk_settings.strict_registrations && cfg!(debug_assertions) && !diagnostics.is_empty() {
+        panic!(
+            "{} registration(s) don't match any LDtk project, and \
+            LdtkSettings::strict_registrations is enabled",
+            diagnostics.len()
+        );
+    }
+}
+
+/// Fires the [LdtkLoadErrorEvent]s queued by the asset loaders.
+pub fn fire_load_error_events(
+    load_errors: Res<LdtkLoadErrorQueue>,
+    mut writer: EventWriter<LdtkLoadErrorEvent>,
+) {
+    for event in load_errors.drain() {
+        writer.send(event);
     }
 }
 
@@ -748,3 +923,473 @@ pub fn fire_level_transformed_events(
         writer.send(LevelEvent::Transformed(id));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::RegisterLdtkObjects, assets::parse_ldtk_json, LdtkSystemLabel};
+    use bevy::asset::AssetPlugin;
+
+    /// An [App] running [process_ldtk_world], with the WorldMap example project already loaded.
+    fn world_map_app() -> (App, Handle<LdtkAsset>) {
+        let project =
+            parse_ldtk_json(include_bytes!("../assets/WorldMap_Free_layout.ldtk"), false).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<LdtkAsset>()
+            .add_asset::<LdtkLevel>()
+            .add_event::<LevelEvent>()
+            .init_resource::<LdtkSettings>()
+            .init_resource::<LdtkProjectHashes>()
+            .init_resource::<LevelMapIds>()
+            .add_system(process_ldtk_world);
+
+        let mut level_assets = app.world.get_resource_mut::<Assets<LdtkLevel>>().unwrap();
+        let level_map = project
+            .levels
+            .iter()
+            .map(|level| {
+                let level_handle = level_assets.add(LdtkLevel {
+                    level: level.clone(),
+                });
+                (level.uid, level_handle)
+            })
+            .collect();
+
+        let ldtk_handle = app
+            .world
+            .get_resource_mut::<Assets<LdtkAsset>>()
+            .unwrap()
+            .add(LdtkAsset {
+                project,
+                tileset_map: HashMap::new(),
+                level_map,
+                level_paths: HashMap::new(),
+            });
+
+        (app, ldtk_handle)
+    }
+
+    fn spawned_level_uids(app: &App, world_entity: Entity) -> HashSet<i32> {
+        app.world
+            .get::<Children>(world_entity)
+            .map(|children| {
+                children
+                    .iter()
+                    .filter_map(|child| app.world.get::<LevelUid>(*child))
+                    .map(|level_uid| level_uid.0)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_new_world_of_loaded_project_spawns_levels() {
+        let (mut app, ldtk_handle) = world_map_app();
+
+        let level_uids: HashSet<i32> = app
+            .world
+            .get_resource::<Assets<LdtkAsset>>()
+            .unwrap()
+            .get(&ldtk_handle)
+            .unwrap()
+            .project
+            .levels
+            .iter()
+            .map(|level| level.uid)
+            .take(2)
+            .collect();
+
+        let spawn_world = |app: &mut App| {
+            let world_entity = app
+                .world
+                .spawn()
+                .insert_bundle((
+                    ldtk_handle.clone(),
+                    LevelSet {
+                        uids: level_uids.clone(),
+                    },
+                ))
+                .id();
+
+            app.update();
+            app.update();
+            world_entity
+        };
+
+        let first_world = spawn_world(&mut app);
+        assert_eq!(spawned_level_uids(&app, first_world), level_uids);
+
+        // The project has already been loaded, so no asset event accompanies this world
+        let second_world = spawn_world(&mut app);
+        assert_eq!(spawned_level_uids(&app, second_world), level_uids);
+    }
+
+    #[test]
+    fn test_unknown_level_uids_dont_block_readiness() {
+        let (mut app, ldtk_handle) = world_map_app();
+        app.add_asset::<Image>()
+            .add_event::<LdtkWorldEvent>()
+            .add_system(update_load_progress);
+
+        let level_uid = app
+            .world
+            .get_resource::<Assets<LdtkAsset>>()
+            .unwrap()
+            .get(&ldtk_handle)
+            .unwrap()
+            .project
+            .levels[0]
+            .uid;
+
+        let world_entity = app
+            .world
+            .spawn()
+            .insert_bundle((
+                ldtk_handle,
+                LevelSet {
+                    uids: HashSet::from([level_uid, 9999]),
+                },
+                LdtkLoadProgress::default(),
+            ))
+            .id();
+
+        app.update();
+        app.update();
+
+        // Spawning is left to process_ldtk_levels, which isn't part of this app
+        let level_entities: Vec<Entity> = app
+            .world
+            .get::<Children>(world_entity)
+            .unwrap()
+            .iter()
+            .copied()
+            .collect();
+        assert_eq!(level_entities.len(), 1);
+        for level_entity in level_entities {
+            app.world
+                .entity_mut(level_entity)
+                .insert(Map::new(0, level_entity));
+        }
+
+        app.update();
+
+        assert_eq!(
+            *app.world.get::<LdtkLoadProgress>(world_entity).unwrap(),
+            LdtkLoadProgress {
+                project_loaded: true,
+                levels_loaded: 1,
+                levels_spawned: 1,
+                levels_total: 1,
+                ..Default::default()
+            }
+        );
+        assert!(app
+            .world
+            .get_resource_mut::<bevy::app::Events<LdtkWorldEvent>>()
+            .unwrap()
+            .drain()
+            .any(|event| matches!(event, LdtkWorldEvent::Ready(e) if e == world_entity)));
+    }
+
+    #[test]
+    fn test_reload_keeps_worldly_entities_of_unchanged_levels() {
+        let (mut app, ldtk_handle) = world_map_app();
+        app.insert_resource(LdtkSettings {
+            despawn_worldly_on_reload: true,
+            ..Default::default()
+        });
+
+        let project = &app
+            .world
+            .get_resource::<Assets<LdtkAsset>>()
+            .unwrap()
+            .get(&ldtk_handle)
+            .unwrap()
+            .project;
+        let (changed_uid, unchanged_uid) = (project.levels[0].uid, project.levels[1].uid);
+
+        let world_entity = app
+            .world
+            .spawn()
+            .insert_bundle((
+                ldtk_handle.clone(),
+                LevelSet {
+                    uids: HashSet::from([changed_uid, unchanged_uid]),
+                },
+            ))
+            .id();
+
+        app.update();
+        app.update();
+        assert_eq!(
+            spawned_level_uids(&app, world_entity),
+            HashSet::from([changed_uid, unchanged_uid])
+        );
+
+        let mut worldly_entity = |spawn_level| {
+            let entity = app
+                .world
+                .spawn()
+                .insert(Worldly {
+                    spawn_level,
+                    ..Default::default()
+                })
+                .id();
+            app.world.entity_mut(world_entity).push_children(&[entity]);
+            entity
+        };
+        let changed_worldly = worldly_entity(changed_uid);
+        let unchanged_worldly = worldly_entity(unchanged_uid);
+
+        app.world
+            .get_resource_mut::<Assets<LdtkAsset>>()
+            .unwrap()
+            .get_mut(&ldtk_handle)
+            .unwrap()
+            .project
+            .levels[0]
+            .bg_color = Color::RED;
+
+        // The modification event is sent at the end of the first update
+        app.update();
+        app.update();
+
+        assert!(app.world.get_entity(changed_worldly).is_none());
+        assert!(app.world.get_entity(unchanged_worldly).is_some());
+        assert_eq!(
+            spawned_level_uids(&app, world_entity),
+            HashSet::from([changed_uid, unchanged_uid])
+        );
+    }
+
+    fn despawn_all_levels(
+        mut commands: Commands,
+        level_query: Query<(Entity, Option<&Map>), With<LevelUid>>,
+        mut level_despawner: LevelDespawner,
+    ) {
+        for (level_entity, map) in level_query.iter() {
+            level_despawner.despawn_level(&mut commands, level_entity, map);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "1 registration(s) don't match any LDtk project")]
+    fn test_registrations_are_checked_once_all_projects_load() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<LdtkAsset>()
+            .init_non_send_resource::<LdtkEntityMap>()
+            .init_non_send_resource::<LdtkIntCellMap>()
+            .insert_resource(LdtkSettings {
+                strict_registrations: true,
+                ..Default::default()
+            })
+            .register_ldtk_entity_for_layer::<EntityInstanceBundle>("Entities", "Player")
+            .register_ldtk_entity_for_layer::<EntityInstanceBundle>("Entities", "Dragon")
+            .add_system(check_registrations);
+
+        let ldtk_asset = |bytes: &[u8]| LdtkAsset {
+            project: parse_ldtk_json(bytes, false).unwrap(),
+            tileset_map: HashMap::new(),
+            level_map: HashMap::new(),
+            level_paths: HashMap::new(),
+        };
+
+        // The "Player" registration only matches the platformer project, and the "Dragon"
+        // registration matches neither
+        let world_map_handle = app
+            .world
+            .get_resource_mut::<Assets<LdtkAsset>>()
+            .unwrap()
+            .add(ldtk_asset(include_bytes!(
+                "../assets/WorldMap_Free_layout.ldtk"
+            )));
+        let platformer_handle: Handle<LdtkAsset> =
+            Handle::weak(bevy::asset::HandleId::random::<LdtkAsset>());
+
+        app.world.spawn().insert(world_map_handle);
+        app.world.spawn().insert(platformer_handle.clone());
+
+        // Checking before the platformer has loaded would report both registrations, failing
+        // the expected panic message
+        for _ in 0..2 {
+            app.update();
+        }
+
+        let _platformer_handle = app
+            .world
+            .get_resource_mut::<Assets<LdtkAsset>>()
+            .unwrap()
+            .set(
+                platformer_handle,
+                ldtk_asset(include_bytes!(
+                    "../assets/Typical_2D_platformer_example.ldtk"
+                )),
+            );
+
+        // Only "Dragon" is reported once both projects have loaded
+        for _ in 0..2 {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn test_despawn_level() {
+        let mut world = World::new();
+
+        let mut map_ids = LevelMapIds::default();
+        let map_id = map_ids.allocate();
+        let mut released_map_ids = map_ids.clone();
+        released_map_ids.release(map_id);
+        world.insert_resource(map_ids);
+
+        let world_entity = world.spawn().id();
+
+        let level_entity = world.spawn().insert(LevelUid(1)).id();
+        world
+            .entity_mut(level_entity)
+            .insert(Map::new(map_id, level_entity))
+            .with_children(|level| {
+                level.spawn().with_children(|layer| {
+                    layer.spawn();
+                });
+                level.spawn().with_children(|layer| {
+                    layer.spawn().insert(EntityInstance::default());
+                    // Not adopted by the world yet, so it belongs to the level
+                    layer.spawn().insert(Worldly::default());
+                });
+            });
+
+        let worldly_entity = world.spawn().insert(Worldly::default()).id();
+
+        world
+            .entity_mut(world_entity)
+            .push_children(&[level_entity, worldly_entity]);
+
+        assert_eq!(world.entities().len(), 8);
+
+        SystemStage::single(despawn_all_levels).run(&mut world);
+
+        assert_eq!(world.entities().len(), 2);
+        assert!(world.get_entity(worldly_entity).is_some());
+        assert_eq!(
+            world
+                .get::<Children>(world_entity)
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![&worldly_entity]
+        );
+
+        assert_eq!(world.get_resource::<LevelMapIds>(), Some(&released_map_ids));
+    }
+
+    #[test]
+    fn test_tileset_usage_updates_are_not_hot_reloads() {
+        let project =
+            parse_ldtk_json(include_bytes!("../assets/WorldMap_Free_layout.ldtk"), false).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Image>()
+            .add_asset::<LdtkAsset>()
+            .add_asset::<LdtkLevel>()
+            .add_event::<LevelEvent>()
+            .init_resource::<LdtkSettings>()
+            .init_resource::<LevelMapIds>()
+            .init_resource::<TilesetImages>()
+            .add_system(process_ldtk_level_reloads.label(LdtkSystemLabel::PreSpawn))
+            .add_system(set_ldtk_texture_filters_to_nearest.after(LdtkSystemLabel::PreSpawn));
+
+        let tileset_handle = app
+            .world
+            .get_resource_mut::<Assets<Image>>()
+            .unwrap()
+            .add(Image::default());
+
+        let mut level_assets = app.world.get_resource_mut::<Assets<LdtkLevel>>().unwrap();
+        let level_map: HashMap<i32, Handle<LdtkLevel>> = project
+            .levels
+            .iter()
+            .map(|level| {
+                let level_handle = level_assets.add(LdtkLevel {
+                    level: level.clone(),
+                });
+                (level.uid, level_handle)
+            })
+            .collect();
+
+        let ldtk_handle = app
+            .world
+            .get_resource_mut::<Assets<LdtkAsset>>()
+            .unwrap()
+            .add(LdtkAsset {
+                project,
+                tileset_map: HashMap::from([(6, tileset_handle.clone())]),
+                level_map: level_map.clone(),
+                level_paths: HashMap::new(),
+            });
+
+        app.world
+            .spawn()
+            .insert(ldtk_handle)
+            .with_children(|world| {
+                for (level_uid, level_handle) in level_map {
+                    world
+                        .spawn()
+                        .insert(LevelUid(level_uid))
+                        .insert(level_handle);
+                }
+            });
+
+        let reloaded_levels = |app: &mut App| {
+            app.world
+                .get_resource_mut::<bevy::app::Events<LevelEvent>>()
+                .unwrap()
+                .drain()
+                .filter(|event| matches!(event, LevelEvent::Reloaded(_)))
+                .count()
+        };
+
+        // The tileset is created, and its usages are updated, which modifies it
+        for _ in 0..4 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world
+                .get_resource::<Assets<Image>>()
+                .unwrap()
+                .get(&tileset_handle)
+                .unwrap()
+                .texture_descriptor
+                .usage,
+            tileset_texture_usages()
+        );
+        assert_eq!(reloaded_levels(&mut app), 0);
+
+        // An actual modification of the tileset
+        app.world
+            .get_resource_mut::<Assets<Image>>()
+            .unwrap()
+            .get_mut(&tileset_handle);
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        assert!(reloaded_levels(&mut app) > 0);
+
+        let tileset_images = app.world.get_resource::<TilesetImages>().unwrap();
+        if cfg!(feature = "atlas") {
+            assert_eq!(tileset_images.get(&tileset_handle), tileset_handle);
+        } else {
+            assert_ne!(tileset_images.get(&tileset_handle), tileset_handle);
+        }
+    }
+}
@@ -23,6 +23,9 @@ use bevy_ecs_tilemap::prelude::*;
 
 use std::collections::HashMap;
 
+#[allow(unused_imports)]
+use crate::components::GridCoords;
+
 /// Tile maker that always creates an invisible tile.
 ///
 /// This function doesn't return a tile maker, it IS one,
@@ -70,24 +73,136 @@ pub(crate) fn tile_pos_to_tile_maker(
         .collect();
 
     move |tile_pos: TilePos| -> Option<Tile> {
-        match grid_tile_map.get(&tile_pos) {
-            Some(tile_instance) => {
-                let (flip_x, flip_y) = match tile_instance.f {
-                    1 => (true, false),
-                    2 => (false, true),
-                    3 => (true, true),
-                    _ => (false, false),
-                };
-
-                Some(Tile {
-                    texture_index: tile_instance.t as u16,
-                    flip_x,
-                    flip_y,
-                    ..Default::default()
+        grid_tile_map.get(&tile_pos).map(tile_instance_to_tile)
+    }
+}
+
+fn tile_instance_to_tile(tile_instance: &TileInstance) -> Tile {
+    let (flip_x, flip_y) = match tile_instance.f {
+        1 => (true, false),
+        2 => (false, true),
+        3 => (true, true),
+        _ => (false, false),
+    };
+
+    Tile {
+        texture_index: tile_instance.t as u16,
+        flip_x,
+        flip_y,
+        color: Color::rgba(1., 1., 1., tile_instance.a),
+        ..Default::default()
+    }
+}
+
+/// A grid of tiles whose cells match the size of the tileset's tiles, rather than the size of the
+/// layer's grid.
+///
+/// Tiles that are larger or smaller than their layer's grid are offset from their grid cell by
+/// the layer's tile pivot.
+/// All tiles in a [PivotedTileGrid] share the same offset, so they can be spawned together as a
+/// single bevy_ecs_tilemap layer, translated by `origin`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PivotedTileGrid {
+    /// Translation of the bottom-left corner of the grid, relative to the layer.
+    pub origin: IVec2,
+    pub size_in_tiles: UVec2,
+    pub tiles: HashMap<TilePos, TileInstance>,
+}
+
+/// Sorts tiles into [PivotedTileGrid]s based off their pivot-adjusted positions.
+///
+/// If the tile size matches the layer's grid size, this will produce at most one grid, whose
+/// origin is at the bottom-left corner of the layer and whose [TilePos]s match the tiles'
+/// [GridCoords].
+pub(crate) fn pivoted_tile_grids(
+    grid_tiles: &[TileInstance],
+    layer_width_in_tiles: i32,
+    layer_height_in_tiles: i32,
+    layer_grid_size: i32,
+    tile_size: i32,
+    tile_pivot: Vec2,
+) -> Vec<PivotedTileGrid> {
+    let mut grid_map: HashMap<IVec2, Vec<(IVec2, &TileInstance)>> = HashMap::new();
+
+    for tile in grid_tiles {
+        let corner = ldtk_tile_pixel_coords_to_translation_pivoted(
+            tile.px,
+            layer_height_in_tiles * layer_grid_size,
+            layer_grid_size,
+            tile_size,
+            tile_pivot,
+        )
+        .round()
+        .as_ivec2();
+
+        let key = IVec2::new(
+            corner.x.rem_euclid(tile_size),
+            corner.y.rem_euclid(tile_size),
+        );
+
+        grid_map.entry(key).or_default().push((corner, tile));
+    }
+
+    let min_size_in_tiles = IVec2::new(
+        (layer_width_in_tiles * layer_grid_size + tile_size - 1) / tile_size,
+        (layer_height_in_tiles * layer_grid_size + tile_size - 1) / tile_size,
+    );
+
+    let mut pivoted_tile_grids: Vec<PivotedTileGrid> = grid_map
+        .into_iter()
+        .map(|(key, tiles)| {
+            // The origin is kept within the first cell unless a tile is pivoted below/left of it
+            let origin = tiles.iter().fold(key, |min, (corner, _)| min.min(*corner));
+
+            let mut size_in_tiles = min_size_in_tiles;
+            let tiles = tiles
+                .into_iter()
+                .map(|(corner, tile)| {
+                    let index = (corner - origin) / tile_size;
+                    size_in_tiles = size_in_tiles.max(index + IVec2::ONE);
+                    (TilePos(index.x as u32, index.y as u32), tile.clone())
                 })
+                .collect();
+
+            PivotedTileGrid {
+                origin,
+                size_in_tiles: size_in_tiles.as_uvec2(),
+                tiles,
             }
-            None => None,
-        }
+        })
+        .collect();
+
+    // HashMap iteration order is arbitrary, so the grids are sorted for deterministic spawning
+    pivoted_tile_grids.sort_by_key(|g| (g.origin.y, g.origin.x));
+
+    pivoted_tile_grids
+}
+
+/// Creates a tile bundle maker that matches the tileset visuals of a [PivotedTileGrid].
+///
+/// Unlike [tile_pos_to_tile_bundle_maker], the [GridCoords] of the resulting bundles refer to the
+/// tile's cell in the layer's grid, rather than its [TilePos] in the [PivotedTileGrid].
+///
+/// Used for spawning Tile, AutoTile and IntGrid layers with AutoTile functionality.
+pub(crate) fn tile_pos_to_pivoted_tile_bundle_maker(
+    pivoted_tile_grid: &PivotedTileGrid,
+    layer_height_in_tiles: i32,
+    layer_grid_size: i32,
+) -> impl FnMut(TilePos) -> Option<TileGridBundle> + '_ {
+    move |tile_pos: TilePos| -> Option<TileGridBundle> {
+        pivoted_tile_grid
+            .tiles
+            .get(&tile_pos)
+            .map(|tile_instance| TileGridBundle {
+                grid_coords: ldtk_grid_coords_to_grid_coords(
+                    tile_instance.px / layer_grid_size,
+                    layer_height_in_tiles,
+                ),
+                tile_bundle: TileBundle {
+                    tile: tile_instance_to_tile(tile_instance),
+                    ..Default::default()
+                },
+            })
     }
 }
 
@@ -183,6 +298,24 @@ pub(crate) fn tile_pos_to_tile_bundle_maker(
     }
 }
 
+/// Returns a tile bundle maker that multiplies the alpha of the provided tile bundle maker's tiles
+/// by the given opacity.
+///
+/// Used for combining layer opacity with the colors and alphas of individual tiles in Tile,
+/// AutoTile, and IntGrid layers.
+pub(crate) fn tile_bundle_maker_with_opacity(
+    mut tile_bundle_maker: impl FnMut(TilePos) -> Option<TileGridBundle>,
+    opacity: f32,
+) -> impl FnMut(TilePos) -> Option<TileGridBundle> {
+    move |tile_pos: TilePos| -> Option<TileGridBundle> {
+        tile_bundle_maker(tile_pos).map(|mut tile_bundle| {
+            let color = &mut tile_bundle.tile_bundle.tile.color;
+            color.set_a(color.a() * opacity);
+            tile_bundle
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,6 +405,137 @@ mod tests {
         assert!(tile_maker(TilePos(2, 1)).unwrap().flip_y);
     }
 
+    #[test]
+    fn test_tile_pos_to_tile_maker_with_alpha() {
+        let grid_tiles = vec![
+            TileInstance {
+                px: IVec2::new(0, 0),
+                a: 0.5,
+                ..Default::default()
+            },
+            TileInstance {
+                px: IVec2::new(32, 0),
+                ..Default::default()
+            },
+        ];
+
+        let mut tile_maker = tile_pos_to_tile_maker(&grid_tiles, 1, 32);
+
+        assert_eq!(tile_maker(TilePos(0, 0)).unwrap().color.a(), 0.5);
+        assert_eq!(tile_maker(TilePos(1, 0)).unwrap().color.a(), 1.);
+    }
+
+    #[test]
+    fn test_tile_bundle_maker_with_opacity() {
+        let grid_tiles = vec![
+            TileInstance {
+                px: IVec2::new(0, 0),
+                a: 0.5,
+                ..Default::default()
+            },
+            TileInstance {
+                px: IVec2::new(32, 0),
+                ..Default::default()
+            },
+        ];
+
+        let mut tile_bundle_maker = tile_bundle_maker_with_opacity(
+            tile_pos_to_tile_bundle_maker(tile_pos_to_tile_maker(&grid_tiles, 1, 32)),
+            0.5,
+        );
+
+        assert_eq!(
+            tile_bundle_maker(TilePos(0, 0))
+                .unwrap()
+                .tile_bundle
+                .tile
+                .color
+                .a(),
+            0.25
+        );
+        assert_eq!(
+            tile_bundle_maker(TilePos(1, 0))
+                .unwrap()
+                .tile_bundle
+                .tile
+                .color
+                .a(),
+            0.5
+        );
+        assert!(tile_bundle_maker(TilePos(2, 0)).is_none());
+    }
+
+    #[test]
+    fn test_pivoted_tile_grids() {
+        let grid_tiles = vec![
+            TileInstance {
+                px: IVec2::new(0, 0),
+                t: 1,
+                ..Default::default()
+            },
+            TileInstance {
+                px: IVec2::new(16, 0),
+                t: 2,
+                ..Default::default()
+            },
+            TileInstance {
+                px: IVec2::new(0, 32),
+                t: 3,
+                ..Default::default()
+            },
+            TileInstance {
+                px: IVec2::new(32, 32),
+                t: 4,
+                ..Default::default()
+            },
+        ];
+
+        let grids = pivoted_tile_grids(&grid_tiles, 4, 4, 16, 32, Vec2::ZERO);
+
+        assert_eq!(grids.len(), 2);
+
+        assert_eq!(grids[0].origin, IVec2::new(0, 0));
+        assert_eq!(grids[0].size_in_tiles, UVec2::new(2, 2));
+        assert_eq!(grids[0].tiles.len(), 3);
+        assert_eq!(grids[0].tiles.get(&TilePos(0, 1)).unwrap().t, 1);
+        assert_eq!(grids[0].tiles.get(&TilePos(0, 0)).unwrap().t, 3);
+        assert_eq!(grids[0].tiles.get(&TilePos(1, 0)).unwrap().t, 4);
+
+        assert_eq!(grids[1].origin, IVec2::new(16, 0));
+        assert_eq!(grids[1].tiles.len(), 1);
+        assert_eq!(grids[1].tiles.get(&TilePos(0, 1)).unwrap().t, 2);
+
+        let mut bundle_maker = tile_pos_to_pivoted_tile_bundle_maker(&grids[1], 4, 16);
+        let bundle = bundle_maker(TilePos(0, 1)).unwrap();
+        assert_eq!(bundle.grid_coords, GridCoords { x: 1, y: 3 });
+        assert_eq!(bundle.tile_bundle.tile.texture_index, 2);
+        assert!(bundle_maker(TilePos(0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_pivoted_tile_grids_matching_grid_size() {
+        let grid_tiles = vec![
+            TileInstance {
+                px: IVec2::new(0, 0),
+                t: 1,
+                ..Default::default()
+            },
+            TileInstance {
+                px: IVec2::new(16, 16),
+                t: 2,
+                ..Default::default()
+            },
+        ];
+
+        let grids = pivoted_tile_grids(&grid_tiles, 3, 2, 16, 16, Vec2::new(0.5, 1.));
+
+        assert_eq!(grids.len(), 1);
+        assert_eq!(grids[0].origin, IVec2::ZERO);
+        assert_eq!(grids[0].size_in_tiles, UVec2::new(3, 2));
+        assert_eq!(grids[0].tiles.get(&TilePos(0, 1)).unwrap().t, 1);
+        assert_eq!(grids[0].tiles.get(&TilePos(1, 0)).unwrap().t, 2);
+    }
+
     #[test]
     fn test_tile_pos_to_int_grid_with_grid_tiles_tile_maker() {
         // Test is designed to have all permutations of tile/intgrid existence:
@@ -7,10 +7,14 @@ use crate::{
 };
 
 use crate::ldtk::*;
-use bevy::prelude::*;
+use bevy::{prelude::*, sprite::Rect};
 use bevy_ecs_tilemap::prelude::*;
 
-use std::{collections::HashMap, hash::Hash};
+use serde::Serialize;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
 
 /// The `int_grid_csv` field of a [LayerInstance] is a 1-dimensional [Vec<i32>].
 /// This function can map the indices of this [Vec] to a corresponding [TilePos].
@@ -65,7 +69,7 @@ pub fn create_layer_definition_map(
 ///
 /// The `entity_definition_map` should be a map of [EntityDefinition] uids to [EntityDefinition]s.
 ///
-/// Internally, this transform is used to place [EntityInstance]s as children of the level.
+/// Internally, this transform is used to place [EntityInstance]s as children of their layer.
 pub fn calculate_transform_from_entity_instance(
     entity_instance: &EntityInstance,
     entity_definition_map: &HashMap<i32, &EntityDefinition>,
@@ -143,6 +147,17 @@ pub fn tile_pos_to_ldtk_grid_coords(tile_pos: TilePos, ldtk_grid_height: i32) ->
     ldtk_coord_conversion_origin_adjusted(tile_coords.as_ivec2(), ldtk_grid_height)
 }
 
+/// Performs translation to [GridCoords] conversion, finding the cell containing the translation.
+///
+/// Assumes that the bottom-left corner of the origin cell is at [Vec2::ZERO].
+/// Translations outside the grid result in negative or out-of-bounds [GridCoords].
+pub fn translation_to_grid_coords(translation: Vec2, grid_size: IVec2) -> GridCoords {
+    (translation / grid_size.as_vec2())
+        .floor()
+        .as_ivec2()
+        .into()
+}
+
 /// Performs LDtk grid coordinate to translation conversion, so that the resulting translation is
 /// in the center of the tile.
 pub fn ldtk_grid_coords_to_translation_centered(
@@ -159,7 +174,7 @@ pub fn ldtk_grid_coords_to_translation_centered(
 ///
 /// Assumes that the bottom-left corner of the origin tile is at [Vec2::ZERO].
 ///
-/// Internally, this transform is used to place [IntGridCell]s as children of the level.
+/// Internally, this transform is used to place [IntGridCell]s as children of their layer.
 pub fn tile_pos_to_translation_centered(tile_pos: TilePos, tile_size: IVec2) -> Vec2 {
     let tile_coords: UVec2 = tile_pos.into();
     let tile_size = tile_size.as_vec2();
@@ -188,6 +203,49 @@ pub fn ldtk_pixel_coords_to_translation_pivoted(
     pivot_point + offset
 }
 
+/// Performs translation to LDtk pixel coordinate conversion, with "pivot" support.
+///
+/// This is the inverse of [ldtk_pixel_coords_to_translation_pivoted], for finding the pixel
+/// coordinates of an entity whose visual is centered at the given translation.
+/// The result is rounded to the nearest pixel.
+pub fn translation_to_ldtk_pixel_coords_pivoted(
+    translation: Vec2,
+    ldtk_pixel_height: i32,
+    entity_size: IVec2,
+    pivot: Vec2,
+) -> IVec2 {
+    let adjusted_pivot = Vec2::new(0.5 - pivot.x, pivot.y - 0.5);
+
+    let offset = entity_size.as_vec2() * adjusted_pivot;
+
+    translation_to_ldtk_pixel_coords((translation - offset).round(), ldtk_pixel_height)
+}
+
+/// Performs LDtk tile pixel coordinate to translation conversion, with "tile pivot" support.
+///
+/// In LDtk, tiles that are larger or smaller than the grid of their layer are positioned relative
+/// to their grid cell according to the `tile_pivot_x` and `tile_pivot_y` of the
+/// [LayerDefinition].
+///
+/// The resulting translation will indicate the location of the bottom-left corner of the tile's
+/// visual, after being pivot-adjusted.
+pub fn ldtk_tile_pixel_coords_to_translation_pivoted(
+    ldtk_coords: IVec2,
+    ldtk_pixel_height: i32,
+    grid_size: i32,
+    tile_size: i32,
+    tile_pivot: Vec2,
+) -> Vec2 {
+    let pivot_offset = Vec2::splat((grid_size - tile_size) as f32) * tile_pivot;
+
+    let top_left = ldtk_coords.as_vec2() + pivot_offset;
+
+    Vec2::new(
+        top_left.x,
+        ldtk_pixel_height as f32 - top_left.y - tile_size as f32,
+    )
+}
+
 /// Similar to [LayerBuilder::new_batch], except it doesn't consume the [LayerBuilder]
 ///
 /// This allows for more methods to be performed on the [LayerBuilder] before building it.
@@ -211,6 +269,68 @@ pub fn set_all_tiles_with_func<T>(
     }
 }
 
+/// Splits the tiles of a layer into "stacks" so that no two tiles in the same stack share a
+/// position.
+///
+/// LDtk allows multiple tiles to occupy the same cell of a layer, but bevy_ecs_tilemap only allows
+/// one tile per [TilePos] per layer.
+/// So, the plugin spawns each stack as its own bevy_ecs_tilemap layer.
+///
+/// Tiles are bucketed by position in a single pass, and the draw order of LDtk is preserved:
+/// the first tile in a cell is placed in the first stack, the second tile in the second stack,
+/// and so on.
+/// Since the plugin spawns stacks with ascending layer ids, and layer ids determine the z
+/// ordering of bevy_ecs_tilemap layers, later tiles in a cell are drawn above earlier ones, like
+/// in the editor.
+///
+/// If `max_stack_depth` is provided, only the top-most `max_stack_depth` tiles of each cell are
+/// kept, and a warning is logged if any tiles were discarded.
+/// The number of stacks is always the number of tiles in the most crowded cell, after capping.
+pub fn layer_grid_tiles(
+    grid_tiles: Vec<TileInstance>,
+    max_stack_depth: Option<usize>,
+) -> Vec<Vec<TileInstance>> {
+    let mut cell_counts: HashMap<IVec2, usize> = HashMap::new();
+    for tile in &grid_tiles {
+        *cell_counts.entry(tile.px).or_default() += 1;
+    }
+
+    let mut discarded_count = 0;
+    let mut cell_depths: HashMap<IVec2, usize> = HashMap::new();
+    let mut stacks: Vec<Vec<TileInstance>> = Vec::new();
+    for tile in grid_tiles {
+        let cell_count = cell_counts[&tile.px];
+        let cell_depth = cell_depths.entry(tile.px).or_default();
+        let order = *cell_depth;
+        *cell_depth += 1;
+
+        let discard_count = match max_stack_depth {
+            Some(max_stack_depth) => cell_count.saturating_sub(max_stack_depth),
+            None => 0,
+        };
+
+        if order < discard_count {
+            discarded_count += 1;
+            continue;
+        }
+
+        let stack_index = order - discard_count;
+        if stack_index >= stacks.len() {
+            stacks.resize_with(stack_index + 1, Vec::new);
+        }
+        stacks[stack_index].push(tile);
+    }
+
+    if discarded_count > 0 {
+        warn!(
+            "Discarded {} stacked tiles that exceeded the maximum stack depth",
+            discarded_count
+        );
+    }
+
+    stacks
+}
+
 /// Wraps `a` and `b` in an [Option] and tries each [Some]/[None] permutation as inputs to `func`,
 /// returning the first non-none result of `func`.
 ///
@@ -253,7 +373,90 @@ where
     A: Hash + Eq + Clone,
     B: Hash + Eq + Clone,
 {
-    try_each_optional_permutation(a, b, |x, y| map.get(&(x, y))).unwrap_or(default)
+    ldtk_map_get(a, b, map).unwrap_or(default)
+}
+
+/// Similar to [ldtk_map_get_or_default], except it returns [None] if no registration matches.
+pub(crate) fn ldtk_map_get<A, B, L>(
+    a: A,
+    b: B,
+    map: &HashMap<(Option<A>, Option<B>), L>,
+) -> Option<&L>
+where
+    A: Hash + Eq + Clone,
+    B: Hash + Eq + Clone,
+{
+    try_each_optional_permutation(a, b, |x, y| map.get(&(x, y)))
+}
+
+/// Hashes the JSON serialization of a value, with the keys of every object in sorted order.
+///
+/// Used to detect changes to LDtk projects, whose types can't implement [Hash] due to their
+/// floating point fields.
+/// Some of them contain [HashMap]s, whose serialization order differs between parses of the same
+/// file, so keys are sorted to keep the hash stable.
+pub(crate) fn hash_serialized<T: Serialize>(value: &T) -> u64 {
+    let value = serde_json::to_value(value).expect("LDtk types should always be serializable");
+
+    let mut hasher = DefaultHasher::new();
+    hash_json_value(&value, &mut hasher);
+    hasher.finish()
+}
+
+fn hash_json_value(value: &serde_json::Value, hasher: &mut DefaultHasher) {
+    use serde_json::Value;
+
+    std::mem::discriminant(value).hash(hasher);
+    match value {
+        Value::Null => (),
+        Value::Bool(b) => b.hash(hasher),
+        Value::Number(n) => n.to_string().hash(hasher),
+        Value::String(s) => s.hash(hasher),
+        Value::Array(values) => {
+            values.len().hash(hasher);
+            for value in values {
+                hash_json_value(value, hasher);
+            }
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+
+            entries.len().hash(hasher);
+            for (key, value) in entries {
+                key.hash(hasher);
+                hash_json_value(value, hasher);
+            }
+        }
+    }
+}
+
+/// Creates a [TextureAtlas] containing the tile of an [EntityInstance]'s Editor Visual.
+///
+/// The atlas is built with an explicit rect matching the tile's `src_rect`, rather than a uniform
+/// grid, so tiles spanning multiple cells and tilesets with padding or spacing are supported.
+/// The tile is always at index `0` of the resulting atlas.
+pub fn texture_atlas_from_entity_tile(
+    tileset: &Handle<Image>,
+    tile: &EntityInstanceTile,
+    tileset_definition: &TilesetDefinition,
+) -> TextureAtlas {
+    let mut texture_atlas = TextureAtlas::new_empty(
+        tileset.clone(),
+        Vec2::new(
+            tileset_definition.px_wid as f32,
+            tileset_definition.px_hei as f32,
+        ),
+    );
+
+    let min = Vec2::new(tile.src_rect[0] as f32, tile.src_rect[1] as f32);
+    let size = Vec2::new(tile.src_rect[2] as f32, tile.src_rect[3] as f32);
+    texture_atlas.add_texture(Rect {
+        min,
+        max: min + size,
+    });
+
+    texture_atlas
 }
 
 /// Creates a [SpriteSheetBundle] from the entity information available to the
@@ -268,23 +471,17 @@ pub fn sprite_sheet_bundle_from_entity_info(
     texture_atlases: &mut Assets<TextureAtlas>,
 ) -> SpriteSheetBundle {
     match (tileset, &entity_instance.tile, tileset_definition) {
-        (Some(tileset), Some(tile), Some(tileset_definition)) => SpriteSheetBundle {
-            texture_atlas: texture_atlases.add(TextureAtlas::from_grid_with_padding(
-                tileset.clone(),
-                Vec2::new(tile.src_rect[2] as f32, tile.src_rect[3] as f32),
-                tileset_definition.c_wid as usize,
-                tileset_definition.c_hei as usize,
-                Vec2::splat(tileset_definition.spacing as f32),
-            )),
-            sprite: TextureAtlasSprite {
-                index: (tile.src_rect[1] / (tile.src_rect[3] + tileset_definition.spacing))
-                    as usize
-                    * tileset_definition.c_wid as usize
-                    + (tile.src_rect[0] / (tile.src_rect[2] + tileset_definition.spacing)) as usize,
+        (Some(tileset), Some(tile), Some(tileset_definition)) if tile.src_rect.len() == 4 => {
+            SpriteSheetBundle {
+                texture_atlas: texture_atlases.add(texture_atlas_from_entity_tile(
+                    tileset,
+                    tile,
+                    tileset_definition,
+                )),
+                sprite: TextureAtlasSprite::new(0),
                 ..Default::default()
-            },
-            ..Default::default()
-        },
+            }
+        }
         _ => {
             warn!("EntityInstance needs a tile, an associated tileset, and an associated tileset definition to be bundled as a SpriteSheetBundle");
             SpriteSheetBundle::default()
@@ -434,6 +631,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_texture_atlas_from_entity_tile() {
+        let tileset_definition = TilesetDefinition {
+            px_wid: 100,
+            px_hei: 60,
+            tile_grid_size: 16,
+            padding: 2,
+            spacing: 2,
+            ..Default::default()
+        };
+
+        // 2x1 tile rect in a padded, spaced tileset
+        let tile = EntityInstanceTile {
+            src_rect: vec![20, 38, 34, 16],
+            ..Default::default()
+        };
+
+        let texture_atlas =
+            texture_atlas_from_entity_tile(&Handle::default(), &tile, &tileset_definition);
+
+        assert_eq!(texture_atlas.size, Vec2::new(100., 60.));
+        assert_eq!(texture_atlas.len(), 1);
+        assert_eq!(texture_atlas.textures[0].min, Vec2::new(20., 38.));
+        assert_eq!(texture_atlas.textures[0].max, Vec2::new(54., 54.));
+    }
+
     #[test]
     fn test_translation_ldtk_pixel_coords_conversion() {
         assert_eq!(
@@ -473,6 +696,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_translation_to_grid_coords() {
+        assert_eq!(
+            translation_to_grid_coords(Vec2::new(48., 80.), IVec2::splat(32)),
+            GridCoords { x: 1, y: 2 }
+        );
+
+        assert_eq!(
+            translation_to_grid_coords(Vec2::new(64., 31.9), IVec2::new(32, 16)),
+            GridCoords { x: 2, y: 1 }
+        );
+
+        assert_eq!(
+            translation_to_grid_coords(Vec2::new(-0.5, 0.), IVec2::splat(16)),
+            GridCoords { x: -1, y: 0 }
+        );
+    }
+
     #[test]
     fn test_tile_pos_to_translation_centered() {
         assert_eq!(
@@ -524,6 +765,125 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_translation_to_ldtk_pixel_coords_pivoted() {
+        assert_eq!(
+            translation_to_ldtk_pixel_coords_pivoted(
+                Vec2::new(48., 48.),
+                128,
+                IVec2::splat(32),
+                Vec2::ZERO
+            ),
+            IVec2::new(32, 64),
+        );
+
+        assert_eq!(
+            translation_to_ldtk_pixel_coords_pivoted(
+                Vec2::new(-0.5, 9.5),
+                10,
+                IVec2::splat(1),
+                Vec2::new(1., 0.)
+            ),
+            IVec2::new(0, 0),
+        );
+
+        // moved off of the pixel grid
+        assert_eq!(
+            translation_to_ldtk_pixel_coords_pivoted(
+                Vec2::new(20.4, -0.3),
+                20,
+                IVec2::splat(5),
+                Vec2::new(0.5, 0.5)
+            ),
+            IVec2::new(20, 20),
+        );
+    }
+
+    #[test]
+    fn test_ldtk_tile_pixel_coords_to_translation_pivoted() {
+        // tile matches the grid, so the pivot has no effect
+        assert_eq!(
+            ldtk_tile_pixel_coords_to_translation_pivoted(
+                IVec2::new(32, 64),
+                128,
+                16,
+                16,
+                Vec2::new(1., 1.)
+            ),
+            Vec2::new(32., 48.),
+        );
+
+        // tile larger than the grid, pivoted to the top-left of its cell
+        assert_eq!(
+            ldtk_tile_pixel_coords_to_translation_pivoted(
+                IVec2::new(16, 16),
+                64,
+                16,
+                32,
+                Vec2::ZERO
+            ),
+            Vec2::new(16., 16.),
+        );
+
+        // tile larger than the grid, pivoted to the bottom-right of its cell
+        assert_eq!(
+            ldtk_tile_pixel_coords_to_translation_pivoted(
+                IVec2::new(16, 16),
+                64,
+                16,
+                32,
+                Vec2::new(1., 1.)
+            ),
+            Vec2::new(0., 32.),
+        );
+
+        // tile smaller than the grid, pivoted to the center of its cell
+        assert_eq!(
+            ldtk_tile_pixel_coords_to_translation_pivoted(
+                IVec2::new(0, 0),
+                32,
+                16,
+                8,
+                Vec2::new(0.5, 0.5)
+            ),
+            Vec2::new(4., 20.),
+        );
+    }
+
+    #[test]
+    fn test_layer_grid_tiles() {
+        let tile = |x: i32, y: i32, t: i32| TileInstance {
+            px: IVec2::new(x, y),
+            t,
+            ..Default::default()
+        };
+
+        let grid_tiles = vec![
+            tile(0, 0, 1),
+            tile(16, 0, 2),
+            tile(0, 0, 3),
+            tile(0, 16, 4),
+            tile(0, 0, 5),
+            tile(16, 0, 6),
+        ];
+
+        let stacks = layer_grid_tiles(grid_tiles.clone(), None);
+        let stack_ids: Vec<Vec<i32>> = stacks
+            .iter()
+            .map(|s| s.iter().map(|t| t.t).collect())
+            .collect();
+        assert_eq!(stack_ids, vec![vec![1, 2, 4], vec![3, 6], vec![5]]);
+
+        let stacks = layer_grid_tiles(grid_tiles, Some(2));
+        let stack_ids: Vec<Vec<i32>> = stacks
+            .iter()
+            .map(|s| s.iter().map(|t| t.t).collect())
+            .collect();
+        assert_eq!(stack_ids, vec![vec![2, 3, 4], vec![5, 6]]);
+
+        assert!(layer_grid_tiles(Vec::new(), None).is_empty());
+    }
+
     #[test]
     fn test_try_each_optional_permutation() {
         fn test_func(a: Option<i32>, b: Option<i32>) -> Option<i32> {
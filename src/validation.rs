@@ -0,0 +1,456 @@
+//! Lint-style checks for LDtk projects, catching data the plugin would otherwise choke on.
+//!
+//! The [LdtkLoader] runs [validate] on every project it loads in debug builds, and the
+//! [LdtkLevelLoader] runs [validate_level] on every external level, logging the resulting
+//! [Diagnostic]s.
+//! These functions are also public so projects can be checked ahead of time, like in CI.
+
+use crate::{
+    app::{LdtkEntityMap, LdtkIntCellMap},
+    assets::LdtkErrorLocation,
+    ldtk::{LayerInstance, LdtkJson, Level, Type},
+};
+use std::{collections::HashSet, fmt};
+
+#[allow(unused_imports)]
+use crate::assets::{LdtkLevelLoader, LdtkLoader};
+
+/// How serious a [Diagnostic] is.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Severity {
+    /// The project will load, but probably not as intended.
+    Warning,
+    /// The project will fail to spawn, or spawn incorrectly.
+    Error,
+}
+
+/// The problem found by a [Diagnostic].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum DiagnosticKind {
+    /// A layer instance refers to a layer definition that doesn't exist.
+    MissingLayerDefinition { layer_def_uid: i32 },
+    /// A layer instance or entity tile refers to a tileset definition that doesn't exist.
+    MissingTileset { tileset_uid: i32 },
+    /// An entity instance refers to an entity definition that doesn't exist.
+    MissingEntityDefinition { def_uid: i32 },
+    /// The `int_grid_csv` of an IntGrid layer doesn't have a value for every cell.
+    IntGridSizeMismatch { expected: usize, actual: usize },
+    /// An [LdtkEntity](crate::app::LdtkEntity) is registered for a layer or entity identifier
+    /// that isn't in the project.
+    UnmatchedEntityRegistration {
+        layer_identifier: Option<String>,
+        entity_identifier: Option<String>,
+    },
+    /// An [LdtkIntCell](crate::app::LdtkIntCell) is registered for a layer identifier or IntGrid
+    /// value that isn't in the project.
+    UnmatchedIntCellRegistration {
+        layer_identifier: Option<String>,
+        value: Option<i32>,
+    },
+}
+
+/// A problem found in an LDtk project by [validate] or [validate_registrations].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// Where in the project the problem is.
+    ///
+    /// This is `None` for problems with registrations, which aren't part of the project.
+    pub location: Option<LdtkErrorLocation>,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match self.kind {
+            DiagnosticKind::UnmatchedEntityRegistration { .. }
+            | DiagnosticKind::UnmatchedIntCellRegistration { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            DiagnosticKind::MissingLayerDefinition { layer_def_uid } => {
+                write!(f, "layer definition {} does not exist", layer_def_uid)?
+            }
+            DiagnosticKind::MissingTileset { tileset_uid } => {
+                write!(f, "tileset definition {} does not exist", tileset_uid)?
+            }
+            DiagnosticKind::MissingEntityDefinition { def_uid } => {
+                write!(f, "entity definition {} does not exist", def_uid)?
+            }
+            DiagnosticKind::IntGridSizeMismatch { expected, actual } => write!(
+                f,
+                "IntGrid layer has {} values, but {} cells",
+                actual, expected
+            )?,
+            DiagnosticKind::UnmatchedEntityRegistration {
+                layer_identifier,
+                entity_identifier,
+            } => write!(
+                f,
+                "LdtkEntity registered for layer {:?} and entity {:?} matches nothing in the project",
+                layer_identifier, entity_identifier
+            )?,
+            DiagnosticKind::UnmatchedIntCellRegistration {
+                layer_identifier,
+                value,
+            } => write!(
+                f,
+                "LdtkIntCell registered for layer {:?} and value {:?} matches nothing in the project",
+                layer_identifier, value
+            )?,
+        }
+
+        if let Some(location) = &self.location {
+            write!(f, " at {}", location)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks the project for data that the plugin assumes to be consistent.
+///
+/// Levels of projects with external levels have no layers until they are loaded separately, so
+/// use [validate_level] to check those.
+pub fn validate(project: &LdtkJson) -> Vec<Diagnostic> {
+    let root = LdtkErrorLocation::root();
+
+    project
+        .levels
+        .iter()
+        .enumerate()
+        .flat_map(|(i, level)| validate_level_at(project, level, root.index("levels", i)))
+        .collect()
+}
+
+/// Checks a single level against the definitions of its project.
+///
+/// Useful for the levels of projects with external levels.
+pub fn validate_level(project: &LdtkJson, level: &Level) -> Vec<Diagnostic> {
+    validate_level_at(project, level, LdtkErrorLocation::root())
+}
+
+fn validate_level_at(
+    project: &LdtkJson,
+    level: &Level,
+    mut location: LdtkErrorLocation,
+) -> Vec<Diagnostic> {
+    location.level = Some(level.identifier.clone());
+
+    let mut diagnostics = Vec::new();
+
+    for (i, layer_instance) in level.layer_instances.iter().flatten().enumerate() {
+        validate_layer(
+            project,
+            layer_instance,
+            location.index("layerInstances", i),
+            &mut diagnostics,
+        );
+    }
+
+    diagnostics
+}
+
+fn validate_layer(
+    project: &LdtkJson,
+    layer_instance: &LayerInstance,
+    location: LdtkErrorLocation,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let defs = &project.defs;
+    let tileset_exists = |uid: i32| defs.tilesets.iter().any(|t| t.uid == uid);
+
+    let mut push = |kind, location: &LdtkErrorLocation| {
+        diagnostics.push(Diagnostic {
+            kind,
+            location: Some(location.clone()),
+        })
+    };
+
+    if !defs
+        .layers
+        .iter()
+        .any(|l| l.uid == layer_instance.layer_def_uid)
+    {
+        push(
+            DiagnosticKind::MissingLayerDefinition {
+                layer_def_uid: layer_instance.layer_def_uid,
+            },
+            &location,
+        );
+    }
+
+    for tileset_uid in [
+        layer_instance.tileset_def_uid,
+        layer_instance.override_tileset_uid,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !tileset_exists(tileset_uid) {
+            push(DiagnosticKind::MissingTileset { tileset_uid }, &location);
+        }
+    }
+
+    if layer_instance.layer_instance_type == Type::IntGrid {
+        let expected = (layer_instance.c_wid * layer_instance.c_hei) as usize;
+        let actual = layer_instance.int_grid_csv.len();
+
+        if expected != actual {
+            push(
+                DiagnosticKind::IntGridSizeMismatch { expected, actual },
+                &location.child("intGridCsv"),
+            );
+        }
+    }
+
+    for (i, entity_instance) in layer_instance.entity_instances.iter().enumerate() {
+        let location = LdtkErrorLocation {
+            entity: Some(entity_instance.identifier.clone()),
+            ..location.index("entityInstances", i)
+        };
+
+        if !defs
+            .entities
+            .iter()
+            .any(|e| e.uid == entity_instance.def_uid)
+        {
+            push(
+                DiagnosticKind::MissingEntityDefinition {
+                    def_uid: entity_instance.def_uid,
+                },
+                &location,
+            );
+        }
+
+        if let Some(tile) = &entity_instance.tile {
+            if !tileset_exists(tile.tileset_uid) {
+                push(
+                    DiagnosticKind::MissingTileset {
+                        tileset_uid: tile.tileset_uid,
+                    },
+                    &location.child("__tile"),
+                );
+            }
+        }
+    }
+}
+
+/// Checks that the identifiers used to register [LdtkEntity](crate::app::LdtkEntity)s and
+/// [LdtkIntCell](crate::app::LdtkIntCell)s exist in any of the given projects.
+///
+/// A typo in a registration otherwise just results in the default bundle being spawned.
+/// Registrations only need to match one of the projects, since an app may load several.
+pub fn validate_registrations(
+    projects: &[&LdtkJson],
+    ldtk_entity_map: &LdtkEntityMap,
+    ldtk_int_cell_map: &LdtkIntCellMap,
+) -> Vec<Diagnostic> {
+    let layer_definitions = || projects.iter().flat_map(|project| &project.defs.layers);
+
+    let layers_of_type = |layer_type: &str| -> HashSet<&str> {
+        layer_definitions()
+            .filter(|l| l.layer_definition_type == layer_type)
+            .map(|l| l.identifier.as_str())
+            .collect()
+    };
+
+    let entity_layers = layers_of_type("Entities");
+    let int_grid_layers = layers_of_type("IntGrid");
+
+    let entities: HashSet<&str> = projects
+        .iter()
+        .flat_map(|project| &project.defs.entities)
+        .map(|e| e.identifier.as_str())
+        .collect();
+
+    let int_grid_value_exists = |layer_identifier: Option<&str>, value: i32| {
+        layer_definitions()
+            .filter(|l| l.layer_definition_type == "IntGrid")
+            .filter(|l| layer_identifier.map(|i| l.identifier == i).unwrap_or(true))
+            .any(|l| l.int_grid_values.iter().any(|v| v.value == value))
+    };
+
+    let mut diagnostics = Vec::new();
+
+    for (layer_identifier, entity_identifier) in ldtk_entity_map.keys() {
+        let layer_exists = layer_identifier
+            .as_deref()
+            .map(|i| entity_layers.contains(i))
+            .unwrap_or(true);
+        let entity_exists = entity_identifier
+            .as_deref()
+            .map(|i| entities.contains(i))
+            .unwrap_or(true);
+
+        if !(layer_exists && entity_exists) {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::UnmatchedEntityRegistration {
+                    layer_identifier: layer_identifier.clone(),
+                    entity_identifier: entity_identifier.clone(),
+                },
+                location: None,
+            });
+        }
+    }
+
+    for (layer_identifier, value) in ldtk_int_cell_map.keys() {
+        let layer_exists = layer_identifier
+            .as_deref()
+            .map(|i| int_grid_layers.contains(i))
+            .unwrap_or(true);
+        let value_exists = value
+            .map(|v| int_grid_value_exists(layer_identifier.as_deref(), v))
+            .unwrap_or(true);
+
+        if !(layer_exists && value_exists) {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::UnmatchedIntCellRegistration {
+                    layer_identifier: layer_identifier.clone(),
+                    value: *value,
+                },
+                location: None,
+            });
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::{PhantomLdtkEntity, PhantomLdtkIntCell},
+        components::{EntityInstanceBundle, IntGridCellBundle},
+    };
+
+    const PLATFORMER: &str = include_str!("../assets/Typical_2D_platformer_example.ldtk");
+
+    fn platformer() -> LdtkJson {
+        serde_json::from_str(PLATFORMER).unwrap()
+    }
+
+    #[test]
+    fn test_valid_project_has_no_diagnostics() {
+        assert_eq!(validate(&platformer()), Vec::new());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut project = platformer();
+
+        let layer_instance = &mut project.levels[0].layer_instances.as_mut().unwrap()[0];
+        layer_instance.layer_def_uid = -1;
+
+        let int_grid_layer = project.levels[1]
+            .layer_instances
+            .as_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|l| l.layer_instance_type == Type::IntGrid)
+            .unwrap();
+        int_grid_layer.int_grid_csv.pop();
+
+        let diagnostics = validate(&project);
+        let kinds: Vec<&DiagnosticKind> = diagnostics.iter().map(|d| &d.kind).collect();
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            kinds[0],
+            &DiagnosticKind::MissingLayerDefinition { layer_def_uid: -1 }
+        );
+        assert_eq!(
+            diagnostics[0].location.as_ref().unwrap().json_path,
+            "$.levels[0].layerInstances[0]"
+        );
+        assert!(matches!(
+            kinds[1],
+            DiagnosticKind::IntGridSizeMismatch { expected, actual } if expected - 1 == *actual
+        ));
+        assert!(diagnostics.iter().all(|d| d.severity() == Severity::Error));
+    }
+
+    #[test]
+    fn test_validate_registrations() {
+        let project = platformer();
+
+        let mut entity_map = LdtkEntityMap::new();
+        entity_map.insert(
+            (None, Some("Player".to_string())),
+            Box::new(PhantomLdtkEntity::<EntityInstanceBundle>::new()),
+        );
+        entity_map.insert(
+            (None, Some("Playa".to_string())),
+            Box::new(PhantomLdtkEntity::<EntityInstanceBundle>::new()),
+        );
+
+        let mut int_cell_map = LdtkIntCellMap::new();
+        int_cell_map.insert(
+            (None, Some(1)),
+            Box::new(PhantomLdtkIntCell::<IntGridCellBundle>::new()),
+        );
+        int_cell_map.insert(
+            (Some("Entities".to_string()), None),
+            Box::new(PhantomLdtkIntCell::<IntGridCellBundle>::new()),
+        );
+
+        let mut diagnostics = validate_registrations(&[&project], &entity_map, &int_cell_map);
+        diagnostics.sort_by_key(|d| d.to_string());
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic {
+                    kind: DiagnosticKind::UnmatchedEntityRegistration {
+                        layer_identifier: None,
+                        entity_identifier: Some("Playa".to_string()),
+                    },
+                    location: None,
+                },
+                Diagnostic {
+                    kind: DiagnosticKind::UnmatchedIntCellRegistration {
+                        layer_identifier: Some("Entities".to_string()),
+                        value: None,
+                    },
+                    location: None,
+                },
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.severity() == Severity::Warning));
+    }
+
+    #[test]
+    fn test_validate_registrations_of_several_projects() {
+        let platformer = platformer();
+        let world_map: LdtkJson =
+            serde_json::from_str(include_str!("../assets/WorldMap_Free_layout.ldtk")).unwrap();
+
+        let mut entity_map = LdtkEntityMap::new();
+        entity_map.insert(
+            (Some("Entities".to_string()), Some("Player".to_string())),
+            Box::new(PhantomLdtkEntity::<EntityInstanceBundle>::new()),
+        );
+
+        let mut int_cell_map = LdtkIntCellMap::new();
+        int_cell_map.insert(
+            (Some("Collisions".to_string()), Some(3)),
+            Box::new(PhantomLdtkIntCell::<IntGridCellBundle>::new()),
+        );
+
+        assert_eq!(
+            validate_registrations(&[&world_map], &entity_map, &int_cell_map).len(),
+            2
+        );
+        assert!(
+            validate_registrations(&[&platformer, &world_map], &entity_map, &int_cell_map)
+                .is_empty()
+        );
+    }
+}